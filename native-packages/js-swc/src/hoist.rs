@@ -1,15 +1,19 @@
-use std::collections::{HashSet, HashMap};
+use std::collections::{HashSet, HashMap, BTreeMap};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use swc_ecmascript::visit::{Fold, FoldWith, Visit, VisitWith, Node};
 use swc_ecmascript::ast::*;
 use swc_atoms::JsWord;
 use swc_common::{DUMMY_SP, SyntaxContext, Mark, sync::Lrc};
+use swc_common::comments::{Comments, SingleThreadedComments};
 use serde::{Deserialize, Serialize};
 
 use crate::utils::{match_member_expr, SourceLocation};
 
 type IdentId = (JsWord, SyntaxContext);
+// Normalized `assert {...}`/`with {...}` import attributes (string-keyed, string-valued
+// per spec), sorted so two equivalent attribute sets always compare/hash identically.
+type ImportAttrs = BTreeMap<JsWord, JsWord>;
 macro_rules! id {
   ($ident: expr) => {
     ($ident.sym.clone(), $ident.span.ctxt)
@@ -26,13 +30,594 @@ macro_rules! hash {
   };
 }
 
-pub fn hoist(module: Module, source_map: Lrc<swc_common::SourceMap>, module_id: &str, decls: HashSet<IdentId>, ignore_mark: Mark, global_mark: Mark) -> (Module, HoistResult) {
-  let mut collect = Collect::new(source_map, decls, ignore_mark);
+// Mirrors swc's module transform `lazy` config: either every dependency is lazy,
+// or only the listed source specifiers are.
+#[derive(Debug, Clone)]
+pub enum HoistLazy {
+  Bool(bool),
+  Sources(HashSet<JsWord>)
+}
+
+impl Default for HoistLazy {
+  fn default() -> Self {
+    HoistLazy::Bool(false)
+  }
+}
+
+impl HoistLazy {
+  fn is_lazy(&self, source: &JsWord) -> bool {
+    match self {
+      HoistLazy::Bool(lazy) => *lazy,
+      HoistLazy::Sources(sources) => sources.contains(source)
+    }
+  }
+
+  // Whether this config could make *any* require lazy, without knowing the module's
+  // actual sources yet. Used to reject lazy + SystemJS output up front, before the
+  // specific lazy sources are known to matter.
+  fn is_enabled(&self) -> bool {
+    match self {
+      HoistLazy::Bool(lazy) => *lazy,
+      HoistLazy::Sources(sources) => !sources.is_empty()
+    }
+  }
+}
+
+// Selects which shape the hoisted module is packaged into. `SystemJs` is built entirely
+// as a post-pass over the already-hoisted module and `HoistResult` metadata (see
+// `to_system_js`), rather than threaded through every `Hoist` fold method, since the
+// SystemJS factory only needs to know the final set of dependencies/imports/exports,
+// not the per-site rewriting logic that produces Parcel's flat namespace scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoistOutputMode {
+  ParcelRegister,
+  SystemJs
+}
+
+impl Default for HoistOutputMode {
+  fn default() -> Self {
+    HoistOutputMode::ParcelRegister
+  }
+}
+
+// Mirrors swc_ecma_transforms_module's `Config { no_interop, strict, .. }`: controls how
+// default imports from CommonJS dependencies are materialized by the linker.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HoistInterop {
+  // When true, `default` binds directly to the target module's namespace object
+  // instead of unwrapping a CJS module's `{ default: ... }` shape.
+  pub no_interop: bool,
+  // When true, default bindings that aren't `no_interop` are flagged so the linker
+  // inserts the `__esModule`-check helper rather than assuming CJS.
+  pub strict: bool,
+}
+
+pub fn hoist(module: Module, source_map: Lrc<swc_common::SourceMap>, module_id: &str, decls: HashSet<IdentId>, ignore_mark: Mark, global_mark: Mark, lazy: HoistLazy, interop: HoistInterop, output_mode: HoistOutputMode, side_effect_free_modules: HashSet<JsWord>, comments: SingleThreadedComments, used_exports: Option<HashSet<JsWord>>) -> (Module, HoistResult) {
+  // Lazy mode and SystemJS output are mutually exclusive (see `to_system_js`). Callers
+  // must not combine them; checked here, before any work is done, so the failure is
+  // obvious at the actual entry point rather than surfacing three calls deep.
+  assert!(!(lazy.is_enabled() && output_mode == HoistOutputMode::SystemJs), "SystemJS output mode does not support lazy-initialized requires");
+
+  let mut collect = Collect::new(source_map, decls, ignore_mark, comments);
   module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
 
-  let mut hoist = Hoist::new(module_id, &collect, global_mark);
+  let mut hoist = Hoist::new(module_id, &collect, global_mark, lazy, interop);
   let module = module.fold_with(&mut hoist);
-  (module, hoist.get_result())
+  let result = hoist.get_result();
+  let module = shake(module, &result, &collect, module_id, &side_effect_free_modules, used_exports.as_ref());
+  let module = match output_mode {
+    HoistOutputMode::ParcelRegister => module,
+    HoistOutputMode::SystemJs => to_system_js(module, &result, module_id)
+  };
+  (module, result)
+}
+
+// The dependency-edge subset of `HoistResult`, gathered by running only the
+// `Collect` visitor (no renaming fold, no codegen). Dependency discovery only needs to
+// enumerate a module's edges, so skipping the fold avoids the far more expensive work
+// of rewriting every reference into Parcel's flat namespace scheme.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModuleInfo {
+  // (source, imported name — "*"/"default"/named, is_dynamic, source location).
+  // Locations are `SourceLocation`s resolved through the source map, not raw byte spans.
+  pub imports: Vec<(JsWord, JsWord, bool, SourceLocation)>,
+  // (exported name, source, imported name — "*" for `export * from`, source location)
+  pub re_exports: Vec<(JsWord, JsWord, JsWord, SourceLocation)>,
+  pub star_sources: Vec<JsWord>,
+  pub exports: Vec<JsWord>,
+  pub has_cjs_exports: bool,
+  // Lets the packager wrap the module in an async factory without running the full
+  // `hoist` pass just to learn this.
+  pub has_top_level_await: bool,
+}
+
+// Lightweight `collect`-only entry point for Parcel's dependency-discovery phase: it
+// only needs a module's import/export edges to build the graph, not the fully hoisted
+// and renamed module `hoist` produces, so it skips `Hoist`'s fold (and `shake`/codegen)
+// entirely.
+pub fn lex_module(module: &Module, source_map: Lrc<swc_common::SourceMap>, decls: HashSet<IdentId>, ignore_mark: Mark, comments: SingleThreadedComments) -> ModuleInfo {
+  let mut collect = Collect::new(source_map, decls, ignore_mark, comments);
+  module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+
+  ModuleInfo {
+    imports: collect.imports.into_iter().map(|(_id, entry)| entry).collect(),
+    re_exports: collect.re_exports,
+    star_sources: collect.star_sources,
+    exports: collect.exports.into_iter().map(|(_id, exported)| exported).collect(),
+    has_cjs_exports: collect.has_cjs_exports,
+    has_top_level_await: collect.has_top_level_await,
+  }
+}
+
+// Counts every `Ident` occurrence in a subtree by symbol text (not full hygienic
+// identity). `shake` only uses this to ask "does anything still reference this name",
+// so collapsing hygiene this way is sound - it only ever overcounts (an unrelated,
+// shadowed same-named binding elsewhere inflates the count), which can cause `shake` to
+// conservatively keep something already dead, never to delete something still live.
+struct UsageCount {
+  counts: HashMap<JsWord, usize>,
+}
+
+impl Visit for UsageCount {
+  fn visit_ident(&mut self, node: &Ident, _parent: &dyn Node) {
+    *self.counts.entry(node.sym.clone()).or_insert(0) += 1;
+  }
+}
+
+fn count_usages(stmts: &[Stmt]) -> HashMap<JsWord, usize> {
+  let mut visitor = UsageCount { counts: HashMap::new() };
+  for stmt in stmts {
+    stmt.visit_with(&Invalid { span: DUMMY_SP } as _, &mut visitor);
+  }
+  visitor.counts
+}
+
+// Side-effect-free per `is_pure_expr`: evaluating the initializer can't itself be
+// observed, so a declaration using one is only kept alive by being referenced elsewhere.
+fn is_pure_expr(expr: &Expr) -> bool {
+  matches!(expr, Expr::Ident(_) | Expr::Lit(_) | Expr::Fn(_) | Expr::Arrow(_) | Expr::Class(_))
+}
+
+// Whether the declarator/function/class bound to the already-hoisted `generated` symbol
+// (e.g. `$abc$export$kept`) should be treated as externally live. Looked up against
+// `exported_symbols` rather than `collect.exports` because by the time `shake` runs the
+// declaration has already been renamed to its exported identifier by `get_export_ident`
+// - `collect.exports`'s keys are pre-rename `IdentId`s that no longer match anything in
+// the folded module, while `exported_symbols` maps straight from an exported name to the
+// generated symbol that now carries it (and, for an aliased local export like
+// `export { x, x as y }`, the same generated symbol can satisfy more than one exported
+// name). With `used_exports: None` (the default `hoist()` pipeline, which has no
+// visibility into the rest of the bundle), every export is conservatively kept, exactly
+// as before this symbol existed. A bundler that already knows which of this module's
+// exports are actually consumed elsewhere can instead pass `Some(requested)`, letting
+// `shake` fold dead-export elimination into the same fixpoint as same-module DCE.
+fn is_requested_export(result: &HoistResult, generated: &JsWord, used_exports: Option<&HashSet<JsWord>>) -> bool {
+  let exported_names = result.exported_symbols.iter()
+    .filter(|(_exported, (sym, _loc))| sym == generated)
+    .map(|(exported, _)| exported);
+
+  match used_exports {
+    Some(used) => exported_names.into_iter().any(|exported| used.contains(exported)),
+    None => exported_names.into_iter().next().is_some()
+  }
+}
+
+// Post-hoist dead code elimination: iteratively deletes top-level declarations and
+// hoisted imports that are provably unreferenced, re-counting after every pass since
+// deleting one dead binding can be exactly what makes another one dead too (a single
+// pass can't catch chains of newly-dead symbols). Modules that had to be wrapped are
+// left untouched, since this file alone can no longer establish what's live.
+//
+// `used_exports` narrows which of this module's exports are allowed to keep a
+// declaration alive on their own: `None` keeps every export (same-module DCE only),
+// `Some(requested)` additionally prunes declarations whose only export isn't in
+// `requested`, letting a dead `$abc$var$`/`$abc$importAsync$` binding built solely to
+// feed an unused export fall out of the same fixpoint once nothing else reaches it.
+fn shake(module: Module, result: &HoistResult, collect: &Collect, module_id: &str, side_effect_free_modules: &HashSet<JsWord>, used_exports: Option<&HashSet<JsWord>>) -> Module {
+  if collect.should_wrap {
+    return module
+  }
+
+  let prefix: JsWord = format!("{}:", module_id).into();
+  let mut body: Vec<ModuleItem> = module.body;
+
+  loop {
+    let stmts: Vec<Stmt> = body.iter().filter_map(|item| match item {
+      ModuleItem::Stmt(stmt) => Some(stmt.clone()),
+      _ => None
+    }).collect();
+    let counts = count_usages(&stmts);
+
+    let mut changed = false;
+    let mut next_body = vec![];
+
+    for item in body {
+      match &item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+          let source: JsWord = match import.src.value.strip_prefix(&*prefix as &str) {
+            Some(source) => source.into(),
+            None => import.src.value.clone()
+          };
+
+          let is_referenced = result.imported_symbols.iter().any(|(generated, (import_source, _key, _loc, _attrs))| {
+            import_source == &source && counts.get(generated).copied().unwrap_or(0) > 0
+          }) || result.dynamic_imports.iter().any(|(generated, (dyn_source, _attrs))| {
+            dyn_source == &source && counts.get(generated).copied().unwrap_or(0) > 0
+          }) || result.re_exports.iter().any(|(_exported, re_source, _orig, _loc)| re_source == &source)
+            || result.star_sources.contains(&source);
+
+          if !is_referenced && side_effect_free_modules.contains(&source) {
+            changed = true;
+            continue
+          }
+        },
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => {
+          let live_decls: Vec<VarDeclarator> = var.decls.iter().filter(|decl| {
+            let ident = match &decl.name {
+              Pat::Ident(ident) => ident,
+              // Destructuring declarators aren't tracked symbol-by-symbol; keep them.
+              _ => return true
+            };
+
+            let is_pure = match &decl.init {
+              Some(init) => is_pure_expr(init),
+              None => true
+            };
+            let is_used = counts.get(&ident.id.sym).copied().unwrap_or(0) > 1;
+            let is_exported = is_requested_export(result, &ident.id.sym, used_exports);
+            let is_non_static = collect.non_static_access.contains(&id!(ident.id));
+
+            !(is_pure && !is_used && !is_exported && !is_non_static)
+          }).cloned().collect();
+
+          if live_decls.len() < var.decls.len() {
+            changed = true;
+          }
+          if live_decls.is_empty() {
+            continue
+          }
+          if live_decls.len() != var.decls.len() {
+            next_body.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl { decls: live_decls, ..var.clone() }))));
+            continue
+          }
+        },
+        ModuleItem::Stmt(Stmt::Decl(Decl::Fn(f))) => {
+          let is_used = counts.get(&f.ident.sym).copied().unwrap_or(0) > 1;
+          let is_exported = is_requested_export(result, &f.ident.sym, used_exports);
+          let is_non_static = collect.non_static_access.contains(&id!(f.ident));
+          if !is_used && !is_exported && !is_non_static {
+            changed = true;
+            continue
+          }
+        },
+        ModuleItem::Stmt(Stmt::Decl(Decl::Class(c))) => {
+          let is_used = counts.get(&c.ident.sym).copied().unwrap_or(0) > 1;
+          let is_exported = is_requested_export(result, &c.ident.sym, used_exports);
+          let is_non_static = collect.non_static_access.contains(&id!(c.ident));
+          if !is_used && !is_exported && !is_non_static {
+            changed = true;
+            continue
+          }
+        },
+        _ => {}
+      }
+
+      next_body.push(item);
+    }
+
+    body = next_body;
+    if !changed {
+      break
+    }
+  }
+
+  Module { span: module.span, shebang: module.shebang, body }
+}
+
+/// Codegen knobs for [`emit`], mirroring the flags Parcel's JS side already exposes for
+/// every other transform (minify, target, source maps) instead of hardcoding them.
+#[derive(Debug, Clone)]
+pub struct CodegenConfig {
+  pub minify: bool,
+  pub target: EsVersion,
+  pub source_maps: bool,
+  pub line_feed: &'static str,
+}
+
+impl Default for CodegenConfig {
+  fn default() -> Self {
+    CodegenConfig {
+      minify: false,
+      target: EsVersion::Es2020,
+      source_maps: false,
+      line_feed: "\n",
+    }
+  }
+}
+
+/// Prints `module` to a source string, returning the raw source map mappings (plus the
+/// `SourceMap` needed to interpret them) alongside it when `config.source_maps` is set, so
+/// callers can compose them with an upstream map rather than us serializing and throwing
+/// position information away here.
+pub fn emit(module: &Module, source_map: Lrc<swc_common::SourceMap>, comments: &swc_common::comments::SingleThreadedComments, config: &CodegenConfig) -> (String, Option<(Vec<(swc_common::BytePos, swc_common::LineCol)>, Lrc<swc_common::SourceMap>)>) {
+  let mut src_map_buf = vec![];
+  let mut buf = vec![];
+  {
+    let writer = Box::new(
+      swc_ecmascript::codegen::text_writer::JsWriter::new(
+        source_map.clone(),
+        config.line_feed,
+        &mut buf,
+        if config.source_maps { Some(&mut src_map_buf) } else { None },
+      )
+    );
+    let codegen_config = swc_ecmascript::codegen::Config { minify: config.minify, target: config.target };
+    let mut emitter = swc_ecmascript::codegen::Emitter {
+      cfg: codegen_config,
+      comments: Some(comments),
+      cm: source_map.clone(),
+      wr: writer,
+    };
+
+    emitter.emit_module(module).unwrap();
+  }
+
+  let code = String::from_utf8(buf).unwrap();
+  let source_map_result = if config.source_maps {
+    Some((src_map_buf, source_map))
+  } else {
+    None
+  };
+  (code, source_map_result)
+}
+
+// Wraps an already-hoisted module (still expressed via Parcel's flat `$id$import$`/
+// `$id$export$` identifiers) into a SystemJS `System.register([deps], function (_export,
+// _context) { ... })` factory, the same shape swc's own `system_js` transform produces.
+// The setters and exports come straight out of `HoistResult` rather than being
+// recomputed, since `Hoist` already collected exactly this information; the dependency
+// list is read off the hoisted import markers (see below).
+//
+// `deps` is built by scanning the surviving hoisted `import "..."` markers in the module
+// body, but lazy mode intentionally omits that marker for lazy-eligible sources (see
+// `add_require`), recording them only in `lazy_imports` - so a lazy dependency would
+// silently disappear from `System.register`'s dependency list. Lazy requires also need
+// their own deferred-initialization shim that this SystemJS factory doesn't produce, so
+// rather than half-support it, the two modes are mutually exclusive for now. `hoist()`
+// already rejects this combination up front based on the `lazy`/`output_mode` params; the
+// assert below is a second line of defense against a hoisted module that ended up with
+// lazy imports some other way.
+fn to_system_js(module: Module, result: &HoistResult, module_id: &str) -> Module {
+  assert!(result.lazy_imports.is_empty(), "SystemJS output mode does not support lazy-initialized requires");
+
+  let prefix: JsWord = format!("{}:", module_id).into();
+  let mut deps: Vec<JsWord> = vec![];
+  let mut body: Vec<Stmt> = vec![];
+
+  for item in module.body {
+    match item {
+      ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+        let source: JsWord = match import.src.value.strip_prefix(&*prefix as &str) {
+          Some(source) => source.into(),
+          None => import.src.value.clone()
+        };
+        if !deps.contains(&source) {
+          deps.push(source);
+        }
+      },
+      ModuleItem::Stmt(stmt) => body.push(stmt),
+      // Everything else should already have been consumed by the normal hoist fold.
+      ModuleItem::ModuleDecl(_) => {}
+    }
+  }
+
+  // The setters array is positional, parallel to `deps`: one function per dependency
+  // that assigns this module's imported bindings from the incoming namespace object.
+  let setters: Vec<Option<ExprOrSpread>> = deps.iter().map(|source| {
+    let module_param: Ident = Ident::new("$$module".into(), DUMMY_SP);
+    let mut stmts = vec![];
+    for (generated, (import_source, key, _loc, _attrs)) in &result.imported_symbols {
+      if import_source != source {
+        continue
+      }
+      let value = if *key == js_word!("*") {
+        Expr::Ident(module_param.clone())
+      } else {
+        Expr::Member(MemberExpr {
+          span: DUMMY_SP,
+          obj: ExprOrSuper::Expr(Box::new(Expr::Ident(module_param.clone()))),
+          prop: Box::new(Expr::Ident(Ident::new(key.clone(), DUMMY_SP))),
+          computed: false
+        })
+      };
+      stmts.push(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+          span: DUMMY_SP,
+          op: AssignOp::Assign,
+          left: PatOrExpr::Expr(Box::new(Expr::Ident(Ident::new(generated.clone(), DUMMY_SP)))),
+          right: Box::new(value)
+        }))
+      }));
+    }
+
+    Some(ExprOrSpread {
+      spread: None,
+      expr: Box::new(Expr::Fn(FnExpr {
+        ident: None,
+        function: Function {
+          params: vec![Param { span: DUMMY_SP, decorators: vec![], pat: Pat::Ident(BindingIdent::from(module_param)) }],
+          decorators: vec![],
+          span: DUMMY_SP,
+          body: Some(BlockStmt { span: DUMMY_SP, stmts }),
+          is_generator: false,
+          is_async: false,
+          type_params: None,
+          return_type: None
+        }
+      }))
+    })
+  }).collect();
+
+  // `exported_symbols`/`dynamic_imports` are keyed by the generated identifier name;
+  // invert them so the body rewrite below can match on the identifiers it finds.
+  let export_idents: HashMap<JsWord, JsWord> = result.exported_symbols.iter()
+    .map(|(exported, (generated, _loc))| (generated.clone(), exported.clone()))
+    .collect();
+
+  let mut export_rewrite = SystemJsExports { export_idents, dynamic_imports: result.dynamic_imports.clone() };
+  let body: Vec<Stmt> = body.into_iter().map(|stmt| stmt.fold_with(&mut export_rewrite)).collect();
+
+  let execute = FnExpr {
+    ident: None,
+    function: Function {
+      params: vec![],
+      decorators: vec![],
+      span: DUMMY_SP,
+      body: Some(BlockStmt { span: DUMMY_SP, stmts: body }),
+      is_generator: false,
+      is_async: result.has_top_level_await,
+      type_params: None,
+      return_type: None
+    }
+  };
+
+  let register_fn = FnExpr {
+    ident: None,
+    function: Function {
+      params: vec![
+        Param { span: DUMMY_SP, decorators: vec![], pat: Pat::Ident(BindingIdent::from(Ident::new("_export".into(), DUMMY_SP))) },
+        Param { span: DUMMY_SP, decorators: vec![], pat: Pat::Ident(BindingIdent::from(Ident::new("_context".into(), DUMMY_SP))) }
+      ],
+      decorators: vec![],
+      span: DUMMY_SP,
+      is_generator: false,
+      is_async: false,
+      type_params: None,
+      return_type: None,
+      body: Some(BlockStmt {
+        span: DUMMY_SP,
+        stmts: vec![
+          Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(Expr::Object(ObjectLit {
+              span: DUMMY_SP,
+              props: vec![
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                  key: PropName::Ident(Ident::new("setters".into(), DUMMY_SP)),
+                  value: Box::new(Expr::Array(ArrayLit { span: DUMMY_SP, elems: setters }))
+                }))),
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                  key: PropName::Ident(Ident::new("execute".into(), DUMMY_SP)),
+                  value: Box::new(Expr::Fn(execute))
+                })))
+              ]
+            })))
+          })
+        ]
+      })
+    }
+  };
+
+  let register_call = Expr::Call(CallExpr {
+    span: DUMMY_SP,
+    callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+      span: DUMMY_SP,
+      obj: ExprOrSuper::Expr(Box::new(Expr::Ident(Ident::new("System".into(), DUMMY_SP)))),
+      prop: Box::new(Expr::Ident(Ident::new("register".into(), DUMMY_SP))),
+      computed: false
+    }))),
+    args: vec![
+      ExprOrSpread { spread: None, expr: Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: deps.iter().map(|source| Some(ExprOrSpread {
+          spread: None,
+          expr: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: source.clone(), kind: StrKind::Synthesized, has_escape: false })))
+        })).collect()
+      })) },
+      ExprOrSpread { spread: None, expr: Box::new(Expr::Fn(register_fn)) }
+    ],
+    type_args: None
+  });
+
+  Module {
+    span: module.span,
+    shebang: module.shebang,
+    body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt { span: DUMMY_SP, expr: Box::new(register_call) }))]
+  }
+}
+
+// Rewrites an already-hoisted module body for the SystemJS factory: live export
+// assignments are routed through `_export(key, value)` so SystemJS's live-binding
+// consumers see reassignments, and dynamic-import placeholders become `_context.import`.
+struct SystemJsExports {
+  export_idents: HashMap<JsWord, JsWord>,
+  dynamic_imports: HashMap<JsWord, (JsWord, ImportAttrs)>,
+}
+
+impl Fold for SystemJsExports {
+  fn fold_expr(&mut self, node: Expr) -> Expr {
+    let node = node.fold_children_with(self);
+    match &node {
+      Expr::Ident(ident) => {
+        if let Some((source, _attrs)) = self.dynamic_imports.get(&ident.sym) {
+          return Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+              span: DUMMY_SP,
+              obj: ExprOrSuper::Expr(Box::new(Expr::Ident(Ident::new("_context".into(), DUMMY_SP)))),
+              prop: Box::new(Expr::Ident(Ident::new("import".into(), DUMMY_SP))),
+              computed: false
+            }))),
+            args: vec![ExprOrSpread { spread: None, expr: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: source.clone(), kind: StrKind::Synthesized, has_escape: false }))) }],
+            type_args: None
+          })
+        }
+      },
+      Expr::Assign(assign) => {
+        if let PatOrExpr::Expr(expr) = &assign.left {
+          if let Expr::Ident(ident) = &**expr {
+            if let Some(key) = self.export_idents.get(&ident.sym) {
+              return wrap_export_call(key, node.clone());
+            }
+          }
+        }
+        if let PatOrExpr::Pat(pat) = &assign.left {
+          if let Pat::Ident(ident) = &**pat {
+            if let Some(key) = self.export_idents.get(&ident.id.sym) {
+              return wrap_export_call(key, node.clone());
+            }
+          }
+        }
+      },
+      _ => {}
+    }
+    node
+  }
+
+  fn fold_var_declarator(&mut self, node: VarDeclarator) -> VarDeclarator {
+    let mut node = node.fold_children_with(self);
+    if let Pat::Ident(ident) = &node.name {
+      if let Some(key) = self.export_idents.get(&ident.id.sym) {
+        if let Some(init) = node.init.take() {
+          node.init = Some(Box::new(wrap_export_call(key, *init)));
+        }
+      }
+    }
+    node
+  }
+}
+
+fn wrap_export_call(key: &JsWord, value: Expr) -> Expr {
+  Expr::Call(CallExpr {
+    span: DUMMY_SP,
+    callee: ExprOrSuper::Expr(Box::new(Expr::Ident(Ident::new("_export".into(), DUMMY_SP)))),
+    args: vec![
+      ExprOrSpread { spread: None, expr: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: key.clone(), kind: StrKind::Synthesized, has_escape: false }))) },
+      ExprOrSpread { spread: None, expr: Box::new(value) }
+    ],
+    type_args: None
+  })
 }
 
 struct Hoist<'a> {
@@ -41,29 +626,88 @@ struct Hoist<'a> {
   global_ctxt: SyntaxContext,
   requires_in_stmt: Vec<ModuleItem>,
   export_decls: HashSet<JsWord>,
-  imported_symbols: HashMap<JsWord, (JsWord, JsWord, SourceLocation)>,
+  imported_symbols: HashMap<JsWord, (JsWord, JsWord, SourceLocation, ImportAttrs)>,
   exported_symbols: HashMap<JsWord, (JsWord, SourceLocation)>,
   re_exports: Vec<(JsWord, JsWord, JsWord, SourceLocation)>,
   self_references: HashSet<JsWord>,
-  dynamic_imports: HashMap<JsWord, JsWord>,
+  dynamic_imports: HashMap<JsWord, (JsWord, ImportAttrs)>,
+  // Distinct `export * from` sources, in declaration order. The bundler's merge step
+  // uses this to detect names exported by more than one star source (which must be
+  // omitted from the namespace, per spec) while explicit/named exports, tracked
+  // separately in `exported_symbols`/`re_exports`, always take precedence over them.
+  star_sources: Vec<JsWord>,
   in_function_scope: bool,
+  has_top_level_await: bool,
+  lazy: HoistLazy,
+  lazy_imports: HashSet<JsWord>,
+  no_interop: bool,
+  strict: bool,
+  needs_default_interop: HashSet<JsWord>,
+  // Import assertions/attributes (e.g. `assert { type: 'json' }`), keyed by the
+  // synthesized `module_id:source` specifier, normalized to string key/value pairs.
+  import_assertions: HashMap<JsWord, ImportAttrs>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HoistResult {
-  imported_symbols: HashMap<JsWord, (JsWord, JsWord, SourceLocation)>,
+  imported_symbols: HashMap<JsWord, (JsWord, JsWord, SourceLocation, ImportAttrs)>,
   exported_symbols: HashMap<JsWord, (JsWord, SourceLocation)>,
   re_exports: Vec<(JsWord, JsWord, JsWord, SourceLocation)>,
+  star_sources: Vec<JsWord>,
   self_references: HashSet<JsWord>,
   wrapped_requires: HashSet<JsWord>,
-  dynamic_imports: HashMap<JsWord, JsWord>,
+  dynamic_imports: HashMap<JsWord, (JsWord, ImportAttrs)>,
   static_cjs_exports: bool,
   has_cjs_exports: bool,
   should_wrap: bool,
+  has_top_level_await: bool,
+  lazy_imports: HashSet<JsWord>,
+  needs_default_interop: HashSet<JsWord>,
+  import_assertions: HashMap<JsWord, ImportAttrs>,
+  resolves: HashSet<JsWord>,
+  optional_requires: HashSet<JsWord>,
+  glob_requires: HashSet<JsWord>,
+  chunk_hints: HashMap<JsWord, JsWord>,
+}
+
+impl HoistResult {
+  // Per spec, a name exported by `export *` from two or more distinct sources is
+  // ambiguous and must be omitted from the namespace, while this module's own explicit
+  // exports (`export {x}`/`export {x} from 'y'`, tracked in `exported_symbols`/
+  // `re_exports`) always shadow a star export of the same name. `hoist` only sees this
+  // one file, so it can't know what `star_sources` themselves export - the caller (the
+  // bundler, which has the whole module graph) supplies that as `source_exports`,
+  // keyed by each entry in `star_sources`. What's returned is the set of names this
+  // module's synthesized namespace must drop as ambiguous.
+  pub fn conflicting_exports(&self, source_exports: &HashMap<JsWord, HashSet<JsWord>>) -> HashSet<JsWord> {
+    let explicit: HashSet<&JsWord> = self.exported_symbols.keys()
+      .chain(self.re_exports.iter().filter(|(exported, ..)| exported != &js_word!("*")).map(|(exported, ..)| exported))
+      .collect();
+
+    let mut seen: HashSet<JsWord> = HashSet::new();
+    let mut conflicting: HashSet<JsWord> = HashSet::new();
+    for source in &self.star_sources {
+      let names = match source_exports.get(source) {
+        Some(names) => names,
+        None => continue
+      };
+      for name in names {
+        // `export *` never re-exports `default`, and an explicit export of the same
+        // name always wins, so neither can be ambiguous here.
+        if *name == js_word!("default") || explicit.contains(name) {
+          continue
+        }
+        if !seen.insert(name.clone()) {
+          conflicting.insert(name.clone());
+        }
+      }
+    }
+    conflicting
+  }
 }
 
 impl<'a> Hoist<'a> {
-  fn new(module_id: &'a str, collect: &'a Collect, global_mark: Mark) -> Self {
+  fn new(module_id: &'a str, collect: &'a Collect, global_mark: Mark, lazy: HoistLazy, interop: HoistInterop) -> Self {
     Hoist {
       module_id,
       collect,
@@ -73,9 +717,17 @@ impl<'a> Hoist<'a> {
       imported_symbols: HashMap::new(),
       exported_symbols: HashMap::new(),
       re_exports: vec![],
+      star_sources: vec![],
       self_references: HashSet::new(),
       dynamic_imports: HashMap::new(),
-      in_function_scope: false
+      in_function_scope: false,
+      has_top_level_await: false,
+      lazy,
+      lazy_imports: HashSet::new(),
+      no_interop: interop.no_interop,
+      strict: interop.strict,
+      needs_default_interop: HashSet::new(),
+      import_assertions: HashMap::new(),
     }
   }
 
@@ -84,12 +736,58 @@ impl<'a> Hoist<'a> {
       imported_symbols: self.imported_symbols,
       exported_symbols: self.exported_symbols,
       re_exports: self.re_exports,
+      star_sources: self.star_sources,
       self_references: self.self_references,
       dynamic_imports: self.dynamic_imports,
       wrapped_requires: self.collect.wrapped_requires.clone(),
       static_cjs_exports: self.collect.static_cjs_exports,
       has_cjs_exports: self.collect.has_cjs_exports,
       should_wrap: self.collect.should_wrap,
+      has_top_level_await: self.has_top_level_await,
+      lazy_imports: self.lazy_imports,
+      needs_default_interop: self.needs_default_interop,
+      import_assertions: self.import_assertions,
+      resolves: self.collect.resolves.clone(),
+      optional_requires: self.collect.optional_requires.clone(),
+      glob_requires: self.collect.glob_requires.clone(),
+      chunk_hints: self.collect.chunk_hints.clone(),
+    }
+  }
+
+  // Records whether the linker needs to insert the `__esModule` interop-check helper for
+  // a binding whose effective key (already resolved by `interop_key`) is `key`.
+  fn resolve_default_interop(&mut self, generated: &JsWord, key: &JsWord) {
+    let is_default: JsWord = "default".into();
+    if *key == is_default && self.strict && !self.no_interop {
+      self.needs_default_interop.insert(generated.clone());
+    }
+  }
+
+  // Only the `default` binding has CJS/ESM interop ambiguity (whether it unwraps a
+  // transpiled module's `{ default: ... }` shape or binds the whole `module.exports`).
+  // Every other named binding is left untouched here so it keeps resolving through the
+  // imported namespace regardless of `no_interop`.
+  fn interop_key(&self, key: &JsWord) -> JsWord {
+    let is_default: JsWord = "default".into();
+    if *key == is_default && self.no_interop {
+      "*".into()
+    } else {
+      key.clone()
+    }
+  }
+
+  // Records an `assert { ... }` / `with { ... }` object literal against the synthesized
+  // specifier so the resolver/loader can key a dependency on its asserted type (e.g.
+  // `type: "json"`).
+  fn record_import_assertions(&mut self, specifier: &JsWord, asserts: &Option<ObjectLit>) {
+    let asserts = match asserts {
+      Some(asserts) => asserts,
+      None => return
+    };
+
+    let attrs = import_attrs_from_obj(asserts);
+    if !attrs.is_empty() {
+      self.import_assertions.insert(specifier.clone(), attrs);
     }
   }
 }
@@ -104,39 +802,126 @@ impl<'a> Fold for Hoist<'a> {
         ModuleItem::ModuleDecl(decl) => {
           match decl {
             ModuleDecl::Import(import) => {
+              // `import type {...} from 'x'` and specifier lists made up entirely of
+              // `import {type X}` entries have no runtime representation to hoist.
+              let all_type_only = !import.specifiers.is_empty() && import.specifiers.iter().all(|s| match s {
+                ImportSpecifier::Named(named) => named.is_type_only,
+                _ => false
+              });
+              if import.type_only || all_type_only {
+                continue
+              }
+
+              let specifier: JsWord = format!("{}:{}", self.module_id, import.src.value).into();
+              self.record_import_assertions(&specifier, &import.asserts);
               hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                 specifiers: vec![],
-                asserts: None,
+                asserts: import.asserts.clone(),
                 span: DUMMY_SP,
-                src: Str { value: format!("{}:{}", self.module_id, import.src.value).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                src: Str { value: specifier, span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
                 type_only: false
               })));
             },
+            ModuleDecl::TsImportEquals(import) => {
+              // `import foo = require('bar')` binds the whole CJS exports object to `foo`,
+              // exactly like `import * as foo from 'bar'`.
+              if import.is_type_only {
+                continue
+              }
+
+              match &import.module_ref {
+                TsModuleRef::TsExternalModuleRef(module_ref) => {
+                  let specifier: JsWord = format!("{}:{}", self.module_id, module_ref.expr.value).into();
+                  hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    specifiers: vec![],
+                    asserts: None,
+                    span: DUMMY_SP,
+                    src: Str { value: specifier, span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                    type_only: false
+                  })));
+                },
+                TsModuleRef::TsEntityName(entity_name) => {
+                  // `import foo = SomeNamespace.Member` aliases an existing value binding
+                  // rather than a module specifier - there's no dependency to hoist, just a
+                  // local `var` so references to `foo` afterward keep resolving instead of
+                  // throwing a `ReferenceError`.
+                  let decl = Decl::Var(VarDecl {
+                    declare: false,
+                    kind: VarDeclKind::Var,
+                    span: DUMMY_SP,
+                    decls: vec![
+                      VarDeclarator {
+                        definite: false,
+                        span: DUMMY_SP,
+                        name: Pat::Ident(BindingIdent::from(import.id.clone())),
+                        init: Some(Box::new(ts_entity_name_to_expr(entity_name)))
+                      }
+                    ]
+                  });
+                  items.push(ModuleItem::Stmt(Stmt::Decl(decl.fold_with(self))));
+                }
+              }
+            },
             ModuleDecl::ExportNamed(export) => {
+              // `export type {...}` is erased entirely; inline `export {type X}` elides
+              // just that specifier.
+              if export.type_only {
+                continue
+              }
+
               if let Some(src) = &export.src {
                 // TODO: skip if already imported.
+                let specifier: JsWord = format!("{}:{}", self.module_id, src.value).into();
+                self.record_import_assertions(&specifier, &export.asserts);
                 hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                   specifiers: vec![],
-                  asserts: None,
+                  asserts: export.asserts.clone(),
                   span: DUMMY_SP,
-                  src: Str { value: format!("{}:{}", self.module_id, src.value).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                  src: Str { value: specifier, span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
                   type_only: false
                 })));
 
                 for specifier in &export.specifiers {
                   match specifier {
                     ExportSpecifier::Named(named) => {
+                      if named.is_type_only {
+                        continue
+                      }
+
+                      let (orig, _) = match_export_name(&named.orig);
                       let exported = match &named.exported {
-                        Some(exported) => exported.sym.clone(),
-                        None => named.orig.sym.clone()
+                        Some(exported) => match_export_name(exported).0,
+                        None => orig.clone()
                       };
-                      self.re_exports.push((exported, src.value.clone(), named.orig.sym.clone(), SourceLocation::from(&self.collect.source_map, named.span)));
+                      self.re_exports.push((exported, src.value.clone(), orig, SourceLocation::from(&self.collect.source_map, named.span)));
                     },
                     ExportSpecifier::Default(default) => {
                       self.re_exports.push((default.exported.sym.clone(), src.value.clone(), js_word!("default"), SourceLocation::from(&self.collect.source_map, default.exported.span)));
                     },
                     ExportSpecifier::Namespace(namespace) => {
-                      self.re_exports.push((namespace.name.sym.clone(), src.value.clone(), "*".into(), SourceLocation::from(&self.collect.source_map, namespace.span)));
+                      // Unlike `export {x} from 'y'`, which the linker resolves by pointing
+                      // consumers straight at `y`'s own generated symbol, `ns` here names the
+                      // whole namespace object of `bar` - there's no single upstream symbol to
+                      // alias to, so bind it locally the same way `import * as ns from 'bar'`
+                      // would, then re-export that binding under its own generated name.
+                      let (name, name_span) = match_export_name(&namespace.name);
+                      self.re_exports.push((name.clone(), src.value.clone(), "*".into(), SourceLocation::from(&self.collect.source_map, namespace.span)));
+
+                      let namespace_ident = self.get_import_ident(namespace.span, &src.value, &"*".into(), SourceLocation::from(&self.collect.source_map, namespace.span));
+                      let export_ident = self.get_export_ident(name_span, &name);
+                      items.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+                        declare: false,
+                        kind: VarDeclKind::Var,
+                        span: DUMMY_SP,
+                        decls: vec![
+                          VarDeclarator {
+                            definite: false,
+                            span: DUMMY_SP,
+                            name: Pat::Ident(BindingIdent::from(export_ident)),
+                            init: Some(Box::new(Expr::Ident(namespace_ident)))
+                          }
+                        ]
+                      }))));
                     }
                   }
                 }
@@ -144,17 +929,22 @@ impl<'a> Fold for Hoist<'a> {
                 for specifier in &export.specifiers {
                   match specifier {
                     ExportSpecifier::Named(named) => {
+                      if named.is_type_only {
+                        continue
+                      }
+
+                      let orig = match_export_name_ident(&named.orig);
                       let exported = match &named.exported {
-                        Some(exported) => exported.sym.clone(),
-                        None => named.orig.sym.clone()
+                        Some(exported) => match_export_name(exported).0,
+                        None => orig.sym.clone()
                       };
-                      if let Some((source, local, _, _)) = self.collect.imports.get(&id!(named.orig)) {
+                      if let Some((source, local, _, _)) = self.collect.imports.get(&id!(orig)) {
                         self.re_exports.push((exported, source.clone(), local.clone(), SourceLocation::from(&self.collect.source_map, named.span)));
                       } else {
                         // A variable will appear only once in the `exports` mapping but
                         // could be exported multiple times with different names.
                         // Find the original exported name, and remap.
-                        let orig_exported = self.collect.exports.get(&id!(named.orig)).unwrap();
+                        let orig_exported = self.collect.exports.get(&id!(orig)).unwrap();
                         let id = if self.collect.should_wrap {
                           Ident::new(orig_exported.clone(), DUMMY_SP)
                         } else {
@@ -169,14 +959,19 @@ impl<'a> Fold for Hoist<'a> {
               }
             },
             ModuleDecl::ExportAll(export) => {
+              let specifier: JsWord = format!("{}:{}", self.module_id, export.src.value).into();
+              self.record_import_assertions(&specifier, &export.asserts);
               hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                 specifiers: vec![],
-                asserts: None,
+                asserts: export.asserts.clone(),
                 span: DUMMY_SP,
-                src: Str { value: format!("{}:{}", self.module_id, export.src.value).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                src: Str { value: specifier, span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
                 type_only: false
               })));
               self.re_exports.push(("*".into(), export.src.value.clone(), "*".into(), SourceLocation::from(&self.collect.source_map, export.span)));
+              if !self.star_sources.contains(&export.src.value) {
+                self.star_sources.push(export.src.value.clone());
+              }
             },
             ModuleDecl::ExportDefaultExpr(export) => {
               let ident = self.get_export_ident(export.span, &"default".into());
@@ -225,6 +1020,27 @@ impl<'a> Fold for Hoist<'a> {
             ModuleDecl::ExportDecl(export) => {
               items.push(ModuleItem::Stmt(Stmt::Decl(export.decl.clone().fold_with(self))));
             },
+            ModuleDecl::TsExportAssignment(export) => {
+              let ident = self.get_export_ident(export.span, &"*".into());
+              let init = export.expr.clone().fold_with(self);
+              if self.requires_in_stmt.len() > 0 {
+                items.append(&mut self.requires_in_stmt);
+                self.requires_in_stmt.clear();
+              }
+              items.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+                declare: false,
+                kind: VarDeclKind::Var,
+                span: DUMMY_SP,
+                decls: vec![
+                  VarDeclarator {
+                    definite: false,
+                    span: DUMMY_SP,
+                    name: Pat::Ident(BindingIdent::from(ident)),
+                    init: Some(init)
+                  }
+                ]
+              }))));
+            },
             _ => {
               items.push(item.clone().fold_with(self))
             }
@@ -250,13 +1066,17 @@ impl<'a> Fold for Hoist<'a> {
                             decls.clear();
                           }
 
-                          items.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
-                            specifiers: vec![],
-                            asserts: None,
-                            span: DUMMY_SP,
-                            src: Str { value: format!("{}:{}", self.module_id, source).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
-                            type_only: false
-                          })));
+                          if self.lazy.is_lazy(&source) {
+                            self.lazy_imports.insert(source.clone());
+                          } else {
+                            items.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                              specifiers: vec![],
+                              asserts: None,
+                              span: DUMMY_SP,
+                              src: Str { value: format!("{}:{}", self.module_id, source).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                              type_only: false
+                            })));
+                          }
                           continue;
                         }
                       }
@@ -273,14 +1093,18 @@ impl<'a> Fold for Hoist<'a> {
                                     items.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))));
                                     decls.clear();
                                   }
-        
-                                  items.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
-                                    specifiers: vec![],
-                                    asserts: None,
-                                    span: DUMMY_SP,
-                                    src: Str { value: format!("{}:{}", self.module_id, source).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
-                                    type_only: false
-                                  })));
+
+                                  if self.lazy.is_lazy(&source) {
+                                    self.lazy_imports.insert(source.clone());
+                                  } else {
+                                    items.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                                      specifiers: vec![],
+                                      asserts: None,
+                                      span: DUMMY_SP,
+                                      src: Str { value: format!("{}:{}", self.module_id, source).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
+                                      type_only: false
+                                    })));
+                                  }
                                   continue;
                                 }
                               }
@@ -372,6 +1196,22 @@ impl<'a> Fold for Hoist<'a> {
     res
   }
 
+  fn fold_arrow_expr(&mut self, node: ArrowExpr) -> ArrowExpr {
+    let in_function_scope = self.in_function_scope;
+    self.in_function_scope = true;
+    let res = node.fold_children_with(self);
+    self.in_function_scope = in_function_scope;
+    res
+  }
+
+  fn fold_for_of_stmt(&mut self, node: ForOfStmt) -> ForOfStmt {
+    // `for await (... of ...)` at module scope is also a form of top-level await.
+    if node.await_token.is_some() && !self.in_function_scope {
+      self.has_top_level_await = true;
+    }
+    node.fold_children_with(self)
+  }
+
   fn fold_expr(&mut self, node: Expr) -> Expr {
     match &node {
       Expr::Member(member) => {
@@ -407,11 +1247,15 @@ impl<'a> Fold for Hoist<'a> {
                   // If there are any non-static accesses of the namespace, don't perform any replacement.
                   // This will be handled in the Ident visitor below, which replaces y -> $id$import$10b1f2ceae7ab64e.
                   if local == "*" && !self.collect.non_static_access.contains(&id!(ident)) && !self.collect.non_static_requires.contains(&source) {
+                    let key = self.interop_key(&key);
                     if *is_async {
                       let name: JsWord = format!("${}$importAsync${:x}${:x}", self.module_id, hash!(source), hash!(key)).into();
-                      self.imported_symbols.insert(name, (source.clone(), key.clone(), SourceLocation::from(&self.collect.source_map, member.span)));
+                      let attrs = self.import_attrs_for(source);
+                      self.imported_symbols.insert(name, (source.clone(), key.clone(), SourceLocation::from(&self.collect.source_map, member.span), attrs));
                     } else {
-                      return Expr::Ident(self.get_import_ident(member.span, &source, &key, SourceLocation::from(&self.collect.source_map, member.span)))
+                      let ident = self.get_import_ident(member.span, &source, &key, SourceLocation::from(&self.collect.source_map, member.span));
+                      self.resolve_default_interop(&ident.sym, &key);
+                      return Expr::Ident(ident)
                     }
                   }
                 }
@@ -428,7 +1272,7 @@ impl<'a> Fold for Hoist<'a> {
               Expr::Call(call) => {
                 // require('foo').bar -> $id$import$foo$bar
                 if let Some(source) = match_require(expr, &self.collect.decls, self.collect.ignore_mark) {
-                  self.add_require(&source);
+                  self.add_require(&source, &ImportAttrs::new());
                   return Expr::Ident(self.get_import_ident(member.span, &source, &key, SourceLocation::from(&self.collect.source_map, member.span)))
                 }
               },
@@ -453,22 +1297,58 @@ impl<'a> Fold for Hoist<'a> {
         }
       },
       Expr::Call(call) => {
+        // import(`./locales/${lang}.json`) -> $parcel$importGlob("abc:./locales/*.json", `./locales/${lang}.json`)
+        // The call must still execute at require-time (the actual module depends on the
+        // runtime value of `lang`), so the original template literal is preserved as an
+        // argument; only the glob pattern used to resolve which modules it can reach is
+        // hoisted into the specifier form the bundler understands.
+        if let Some((pattern, _span)) = match_dynamic_import_glob(&node, self.collect.ignore_mark) {
+          let specifier: JsWord = format!("{}:{}", self.module_id, pattern).into();
+          let mut call = call.clone();
+          call.callee = ExprOrSuper::Expr(Box::new(Expr::Ident(Ident::new("$parcel$importGlob".into(), call.span))));
+          call.args.insert(0, ExprOrSpread { spread: None, expr: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: specifier, kind: StrKind::Synthesized, has_escape: false }))) });
+          return Expr::Call(call).fold_children_with(self)
+        }
+
+        // require.resolve('foo') -> require.resolve("abc:foo")
+        // The call itself is preserved (it still needs to run at require-time to produce
+        // a path), only the specifier is rewritten to the hoisted `module_id:source` form.
+        if let Some(source) = match_require_resolve(&node, &self.collect.decls, self.collect.ignore_mark) {
+          let specifier: JsWord = format!("{}:{}", self.module_id, source).into();
+          let mut call = call.clone();
+          call.args = vec![ExprOrSpread { spread: None, expr: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: specifier, kind: StrKind::Synthesized, has_escape: false }))) }];
+          return Expr::Call(call)
+        }
+
         // require('foo') -> $id$import$foo
         if let Some(source) = match_require(&node, &self.collect.decls, self.collect.ignore_mark) {
-          self.add_require(&source);
+          self.add_require(&source, &ImportAttrs::new());
           return Expr::Ident(self.get_import_ident(call.span, &source, &("*".into()), SourceLocation::from(&self.collect.source_map, call.span)))
         }
 
         if let Some(source) = match_import(&node, self.collect.ignore_mark) {
-          self.add_require(&source);
+          // `import(spec, { with: { type: "json" } })` carries its own attributes, kept
+          // local to this call site rather than recorded into the shared, specifier-keyed
+          // `import_assertions` map: a static import of the same source may carry a
+          // different (or no) `.asserts` clause, and the two must not collapse.
+          let attrs = match_dynamic_import_attrs(call);
+          self.add_require(&source, &attrs);
           let name: JsWord = format!("${}$importAsync${:x}", self.module_id, hash!(source)).into();
-          self.dynamic_imports.insert(name.clone(), source.clone());
+          self.dynamic_imports.insert(name.clone(), (source.clone(), attrs.clone()));
           if self.collect.non_static_requires.contains(&source) || self.collect.should_wrap {
-            self.imported_symbols.insert(name.clone(), (source.clone(), "*".into(), SourceLocation::from(&self.collect.source_map, call.span)));
+            self.imported_symbols.insert(name.clone(), (source.clone(), "*".into(), SourceLocation::from(&self.collect.source_map, call.span), attrs));
           }
           return Expr::Ident(Ident::new(name, call.span))
         }
       },
+      Expr::Await(_await_expr) => {
+        // A top-level `await` (i.e. one that isn't inside a function/arrow body, which set
+        // in_function_scope via fold_function/fold_class/fold_arrow_expr) means the module must be treated
+        // as an async module by the packager.
+        if !self.in_function_scope {
+          self.has_top_level_await = true;
+        }
+      },
       Expr::This(this) => {
         if !self.in_function_scope {
           // If ESM, replace `this` with `undefined`, otherwise with the CJS exports object.
@@ -544,13 +1424,18 @@ impl<'a> Fold for Hoist<'a> {
         if *is_async {
           if local != "*" {
             let name: JsWord = format!("${}$importAsync${:x}${:x}", self.module_id, hash!(source), hash!(local)).into();
-            self.imported_symbols.insert(name, (source.clone(), local.clone(), loc.clone()));
+            let attrs = self.import_attrs_for(source);
+            self.imported_symbols.insert(name, (source.clone(), local.clone(), loc.clone(), attrs));
           } else if self.collect.non_static_access.contains(&id!(node)) {
             let name: JsWord = format!("${}$importAsync${:x}", self.module_id, hash!(source)).into();
-            self.imported_symbols.insert(name, (source.clone(), "*".into(), loc.clone()));
+            let attrs = self.import_attrs_for(source);
+            self.imported_symbols.insert(name, (source.clone(), "*".into(), loc.clone(), attrs));
           }
         } else {
-          return self.get_import_ident(node.span, source, local, loc.clone());
+          let key = self.interop_key(local);
+          let ident = self.get_import_ident(node.span, source, &key, loc.clone());
+          self.resolve_default_interop(&ident.sym, &key);
+          return ident;
         }
       }
     }
@@ -739,10 +1624,34 @@ impl<'a> Fold for Hoist<'a> {
 }
 
 impl<'a> Hoist<'a> {
-  fn add_require(&mut self, source: &JsWord) {
+  // Looks up the attributes `record_import_assertions` recorded for a source's static
+  // import/export against its hoisted specifier, so every entry that references a source
+  // can carry the same attribute set without re-deriving it. A dynamic `import()`'s own
+  // attributes are not recorded there (they stay on its `dynamic_imports` entry), so
+  // they are never returned here.
+  fn import_attrs_for(&self, source: &JsWord) -> ImportAttrs {
+    let specifier: JsWord = format!("{}:{}", self.module_id, source).into();
+    self.import_assertions.get(&specifier).cloned().unwrap_or_default()
+  }
+
+  fn add_require(&mut self, source: &JsWord, attrs: &ImportAttrs) {
+    // In lazy mode, a wrapped require whose members are all accessed statically can be
+    // deferred: the module factory only runs the first time its imported namespace
+    // identifier is touched, via a memoized getter the linker generates from
+    // `lazy_imports`, instead of the eager synthetic import normally hoisted here.
+    // Non-static accesses and side-effect-only requires must stay eager to preserve
+    // their observable ordering, even in lazy mode.
+    if self.lazy.is_lazy(source)
+      && self.collect.wrapped_requires.contains(source)
+      && !self.collect.non_static_requires.contains(source)
+    {
+      self.lazy_imports.insert(source.clone());
+      return;
+    }
+
     self.requires_in_stmt.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
       specifiers: vec![],
-      asserts: None,
+      asserts: import_attrs_to_obj(attrs),
       span: DUMMY_SP,
       src: Str { value: format!("{}:{}", self.module_id, source).into(), span: DUMMY_SP, kind: StrKind::Synthesized, has_escape: false },
       type_only: false
@@ -755,15 +1664,20 @@ impl<'a> Hoist<'a> {
     } else {
       format!("${}$import${:x}${:x}", self.module_id, hash!(source), hash!(local)).into()
     };
-    self.imported_symbols.insert(new_name.clone(), (source.clone(), local.clone(), loc));
+    let attrs = self.import_attrs_for(source);
+    self.imported_symbols.insert(new_name.clone(), (source.clone(), local.clone(), loc, attrs));
     return Ident::new(new_name, span)
   }
 
   fn get_export_ident(&mut self, span: swc_common::Span, exported: &JsWord) -> Ident {
     let new_name: JsWord = if exported == "*" {
       format!("${}$exports", self.module_id).into()
-    } else {
+    } else if is_identifier(exported) {
       format!("${}$export${}", self.module_id, exported).into()
+    } else {
+      // Arbitrary module export names (ES2022, e.g. `export { x as "a-b" }`) aren't valid
+      // identifier characters, so hash them the same way import bindings already are.
+      format!("${}$export${:x}", self.module_id, hash!(exported)).into()
     };
 
     self.exported_symbols.entry(exported.clone()).or_insert((new_name.clone(), SourceLocation::from(&self.collect.source_map, span)));
@@ -778,11 +1692,17 @@ macro_rules! visit_fn {
   ($self: ident, $node: ident) => {
     let in_module_this = $self.in_module_this;
     let in_function = $self.in_function;
+    // A `try` block only guards the `require()` calls lexically inside it, not ones in a
+    // function merely declared there - reset so a call invoked elsewhere, unguarded, isn't
+    // misclassified as optional.
+    let in_try = $self.in_try;
     $self.in_module_this = false;
     $self.in_function = true;
+    $self.in_try = false;
     $node.visit_children_with($self);
     $self.in_module_this = in_module_this;
     $self.in_function = in_function;
+    $self.in_try = in_try;
   };
 }
 
@@ -799,14 +1719,35 @@ pub struct Collect {
   non_static_access: HashSet<IdentId>,
   non_static_requires: HashSet<JsWord>,
   wrapped_requires: HashSet<JsWord>,
+  // Sources referenced only via `require.resolve('x')`, which yields a resolved path
+  // rather than the module's exports and so is never treated as pulling in its body.
+  pub resolves: HashSet<JsWord>,
+  // Sources whose `require`/`import()`/`require.resolve` call is guarded by a `try`
+  // block, meaning the module may legitimately be missing at build/run time.
+  pub optional_requires: HashSet<JsWord>,
+  // Glob patterns derived from dynamic `import()` calls whose specifier is a template
+  // literal with interpolated expressions, e.g. `import(`./locales/${lang}.json`)`.
+  pub glob_requires: HashSet<JsWord>,
+  // Loading-priority hints (from a leading `/* prefetch */`/`/* preload */` comment on
+  // the `import()` call), keyed by the glob pattern they apply to.
+  pub chunk_hints: HashMap<JsWord, JsWord>,
+  // (exported name, source, imported name — "*" for a star re-export) for every
+  // `export {...} from`/`export * from`, in declaration order. Populated here (rather
+  // than only during `Hoist`'s fold, as before) so `lex_module` can read dependency
+  // edges off a `Collect` pass alone.
+  pub re_exports: Vec<(JsWord, JsWord, JsWord, SourceLocation)>,
+  pub star_sources: Vec<JsWord>,
+  comments: SingleThreadedComments,
+  in_try: bool,
   in_module_this: bool,
   in_top_level: bool,
   in_export_decl: bool,
   in_function: bool,
+  pub has_top_level_await: bool,
 }
 
 impl Collect {
-  pub fn new(source_map: Lrc<swc_common::SourceMap>, decls: HashSet<IdentId>, ignore_mark: Mark) -> Self {
+  pub fn new(source_map: Lrc<swc_common::SourceMap>, decls: HashSet<IdentId>, ignore_mark: Mark, comments: SingleThreadedComments) -> Self {
     Collect {
       source_map,
       decls,
@@ -820,10 +1761,25 @@ impl Collect {
       non_static_access: HashSet::new(),
       non_static_requires: HashSet::new(),
       wrapped_requires: HashSet::new(),
+      resolves: HashSet::new(),
+      optional_requires: HashSet::new(),
+      glob_requires: HashSet::new(),
+      chunk_hints: HashMap::new(),
+      re_exports: vec![],
+      star_sources: vec![],
+      comments,
+      in_try: false,
       in_module_this: true,
       in_top_level: true,
       in_export_decl: false,
-      in_function: false
+      in_function: false,
+      has_top_level_await: false
+    }
+  }
+
+  fn mark_optional(&mut self, source: &JsWord) {
+    if self.in_try {
+      self.optional_requires.insert(source.clone());
     }
   }
 }
@@ -852,6 +1808,21 @@ impl Visit for Collect {
     self.in_function = in_function;
   }
 
+  fn visit_await_expr(&mut self, node: &AwaitExpr, _parent: &dyn Node) {
+    if !self.in_function {
+      self.has_top_level_await = true;
+    }
+    node.visit_children_with(self);
+  }
+
+  fn visit_for_of_stmt(&mut self, node: &ForOfStmt, _parent: &dyn Node) {
+    // `for await (... of ...)` at module scope is also a form of top-level await.
+    if node.await_token.is_some() && !self.in_function {
+      self.has_top_level_await = true;
+    }
+    node.visit_children_with(self);
+  }
+
   fn visit_module_item(&mut self, node: &ModuleItem, _parent: &dyn Node) {
     match node {
       ModuleItem::ModuleDecl(_decl) => {
@@ -887,11 +1858,22 @@ impl Visit for Collect {
   }
 
   fn visit_import_decl(&mut self, node: &ImportDecl, _parent: &dyn Node) {
+    // `import type {...} from 'x'` is erased entirely: it has no runtime representation,
+    // so it must never produce a tracked import or a hoisted `import "abc:x"`.
+    if node.type_only {
+      return
+    }
+
     for specifier in &node.specifiers {
       match specifier {
         ImportSpecifier::Named(named) => {
+          // Inline `import {type X}` elides just that specifier, leaving its siblings alone.
+          if named.is_type_only {
+            continue
+          }
+
           let imported = match &named.imported {
-            Some(imported) => imported.sym.clone(),
+            Some(imported) => match_export_name(imported).0,
             None => named.local.sym.clone()
           };
           self.imports.insert(id!(named.local), (node.src.value.clone(), imported, false, SourceLocation::from(&self.source_map, named.span)));
@@ -906,30 +1888,88 @@ impl Visit for Collect {
     }
   }
 
-  fn visit_named_export(&mut self, node: &NamedExport, _parent: &dyn Node) {
-    if node.src.is_some() {
+  fn visit_ts_import_equals_decl(&mut self, node: &TsImportEqualsDecl, _parent: &dyn Node) {
+    // `import foo = require('bar')` is TypeScript's CJS-flavored import form: `foo` binds
+    // to the whole `bar` exports object, exactly like `import * as foo from 'bar'`.
+    if node.is_type_only {
+      return
+    }
+
+    if let TsModuleRef::TsExternalModuleRef(module_ref) = &node.module_ref {
+      self.imports.insert(id!(node.id), (module_ref.expr.value.clone(), "*".into(), false, SourceLocation::from(&self.source_map, node.span)));
+    }
+  }
+
+  fn visit_named_export(&mut self, node: &NamedExport, _parent: &dyn Node) {
+    if node.type_only {
+      return
+    }
+
+    // `export {x} from 'y'` / `export {x as z} from 'y'` re-exports a binding from
+    // another module without introducing a local one, so it's tracked as a re-export
+    // (for the module lexer's dependency graph) rather than in `exports`, which only
+    // covers names bound to a local identifier.
+    if let Some(src) = &node.src {
+      for specifier in &node.specifiers {
+        match specifier {
+          ExportSpecifier::Named(named) => {
+            if named.is_type_only {
+              continue
+            }
+
+            let (orig, orig_span) = match_export_name(&named.orig);
+            let exported = match &named.exported {
+              Some(exported) => match_export_name(exported).0,
+              None => orig.clone()
+            };
+            self.re_exports.push((exported, src.value.clone(), orig, SourceLocation::from(&self.source_map, orig_span)));
+          },
+          ExportSpecifier::Default(default) => {
+            self.re_exports.push((default.exported.sym.clone(), src.value.clone(), js_word!("default"), SourceLocation::from(&self.source_map, default.exported.span)));
+          },
+          ExportSpecifier::Namespace(namespace) => {
+            let (name, name_span) = match_export_name(&namespace.name);
+            self.re_exports.push((name, src.value.clone(), "*".into(), SourceLocation::from(&self.source_map, name_span)));
+          }
+        }
+      }
       return
     }
 
     for specifier in &node.specifiers {
       match specifier {
         ExportSpecifier::Named(named) => {
+          if named.is_type_only {
+            continue
+          }
+
+          let orig = match_export_name_ident(&named.orig);
           let exported = match &named.exported {
-            Some(exported) => exported.sym.clone(),
-            None => named.orig.sym.clone()
+            Some(exported) => match_export_name(exported).0,
+            None => orig.sym.clone()
           };
-          self.exports.entry(id!(named.orig)).or_insert(exported);
+          self.exports.entry(id!(orig)).or_insert(exported);
         },
         ExportSpecifier::Default(default) => {
           self.exports.entry(id!(default.exported)).or_insert(js_word!("default"));
         },
         ExportSpecifier::Namespace(namespace) => {
-          self.exports.entry(id!(namespace.name)).or_insert("*".into());
+          // `export * as "ns";` without a `from` clause isn't valid syntax, so the
+          // namespace name here always resolves to a real local binding.
+          let name = match_export_name_ident(&namespace.name);
+          self.exports.entry(id!(name)).or_insert("*".into());
         }
       }
     }
   }
 
+  fn visit_export_all(&mut self, node: &ExportAll, _parent: &dyn Node) {
+    self.re_exports.push(("*".into(), node.src.value.clone(), "*".into(), SourceLocation::from(&self.source_map, node.span)));
+    if !self.star_sources.contains(&node.src.value) {
+      self.star_sources.push(node.src.value.clone());
+    }
+  }
+
   fn visit_export_decl(&mut self, node: &ExportDecl, _parent: &dyn Node) {
     match &node.decl {
       Decl::Class(class) => {
@@ -1114,14 +2154,29 @@ impl Visit for Collect {
     }
   }
 
+  fn visit_ts_export_assignment(&mut self, node: &TsExportAssignment, _parent: &dyn Node) {
+    // TypeScript `export = expr` is CJS-style: the whole module becomes the exported value,
+    // just like `module.exports = expr`.
+    self.has_cjs_exports = true;
+    self.static_cjs_exports = false;
+    node.visit_children_with(self);
+  }
+
   fn visit_var_declarator(&mut self, node: &VarDeclarator, _parent: &dyn Node) {
     // if init is a require call, record static accesses
     if let Some(init) = &node.init {
       if let Some(source) = self.match_require(init) {
+        self.mark_optional(&source);
         self.add_pat_imports(&node.name, &source, false);
         return;
       }
 
+      if let Some(source) = match_require_resolve(init, &self.decls, self.ignore_mark) {
+        self.resolves.insert(source.clone());
+        self.mark_optional(&source);
+        return;
+      }
+
       match &**init {
         Expr::Member(member) => {
           match &member.obj {
@@ -1146,6 +2201,7 @@ impl Visit for Collect {
                   _ => PropName::Computed(ComputedPropName { span: DUMMY_SP, expr: Box::new(*expr.clone()) })
                 };
 
+                self.mark_optional(&source);
                 self.add_pat_imports(&Pat::Object(ObjectPat {
                   optional: false,
                   span: DUMMY_SP,
@@ -1156,7 +2212,7 @@ impl Visit for Collect {
                   })]
                 }), &source, false);
                 return
-              }    
+              }
             },
             _ => {}
           }
@@ -1165,6 +2221,7 @@ impl Visit for Collect {
           // let x = await import('foo');
           // let {x} = await import('foo');
           if let Some(source) = match_import(&*await_exp.arg, self.ignore_mark) {
+            self.mark_optional(&source);
             self.add_pat_imports(&node.name, &source, true);
             return
           }
@@ -1176,18 +2233,53 @@ impl Visit for Collect {
     node.visit_children_with(self);
   }
 
+  fn visit_try_stmt(&mut self, node: &TryStmt, _parent: &dyn Node) {
+    // Only requires actually guarded by the `try` block itself are optional: the
+    // `catch`/`finally` bodies run whether or not the try block's requires threw.
+    let old_in_try = self.in_try;
+    self.in_try = true;
+    node.block.visit_with(node, self);
+    self.in_try = old_in_try;
+
+    if let Some(handler) = &node.handler {
+      handler.visit_with(node, self);
+    }
+    if let Some(finalizer) = &node.finalizer {
+      finalizer.visit_with(node, self);
+    }
+  }
+
   fn visit_call_expr(&mut self, node: &CallExpr, _parent: &dyn Node) {
+    if let Some((pattern, span)) = match_dynamic_import_glob(&Expr::Call(node.clone()), self.ignore_mark) {
+      self.glob_requires.insert(pattern.clone());
+      self.mark_optional(&pattern);
+      if let Some(hint) = match_chunk_hint(&self.comments, span.lo) {
+        self.chunk_hints.insert(pattern, hint);
+      }
+      node.visit_children_with(self);
+      return
+    }
+
+    if let Some(source) = match_require_resolve(&Expr::Call(node.clone()), &self.decls, self.ignore_mark) {
+      self.resolves.insert(source.clone());
+      self.mark_optional(&source);
+      node.visit_children_with(self);
+      return
+    }
+
     // If we reached this visitor, this is a non-top-level require that isn't in a variable
     // declaration. We need to wrap the referenced module to preserve side effect ordering.
     if let Some(source) = self.match_require(&Expr::Call(node.clone())) {
       self.wrapped_requires.insert(source.clone());
+      self.mark_optional(&source);
     }
 
     if let Some(source) = match_import(&Expr::Call(node.clone()), self.ignore_mark) {
       self.non_static_requires.insert(source.clone());
       self.wrapped_requires.insert(source.clone());
+      self.mark_optional(&source);
     }
-    
+
     match &node.callee {
       ExprOrSuper::Expr(expr) => {
         match &**expr {
@@ -1224,6 +2316,7 @@ impl Visit for Collect {
                         _ => None
                       };
 
+                      self.mark_optional(&source);
                       if let Some(param) = param {
                         self.add_pat_imports(param, &source, true);
                       } else {
@@ -1361,6 +2454,191 @@ fn match_require(node: &Expr, decls: &HashSet<IdentId>, ignore_mark: Mark) -> Op
   }
 }
 
+// Matches `require.resolve('x')`, which yields a resolved path rather than the
+// module's exports, so it must be tracked separately from a normal `require('x')`.
+fn match_require_resolve(node: &Expr, decls: &HashSet<IdentId>, ignore_mark: Mark) -> Option<JsWord> {
+  match node {
+    Expr::Call(call) => {
+      match &call.callee {
+        ExprOrSuper::Expr(expr) => {
+          match &**expr {
+            Expr::Member(member) => {
+              let is_require_resolve = match &member.obj {
+                ExprOrSuper::Expr(obj) => match &**obj {
+                  Expr::Ident(ident) => ident.sym == js_word!("require") && !decls.contains(&id!(ident)) && !is_marked(ident.span, ignore_mark),
+                  _ => false
+                },
+                _ => false
+              } && match &*member.prop {
+                Expr::Ident(prop) => {
+                  let resolve: JsWord = "resolve".into();
+                  !member.computed && prop.sym == resolve
+                },
+                _ => false
+              };
+
+              if is_require_resolve {
+                if let Some(arg) = call.args.get(0) {
+                  if let Expr::Lit(Lit::Str(str_)) = &*arg.expr {
+                    return Some(str_.value.clone())
+                  }
+                }
+              }
+
+              None
+            },
+            _ => None
+          }
+        },
+        _ => None
+      }
+    },
+    _ => None
+  }
+}
+
+// Converts a dynamic `import()`'s template-literal specifier into a glob pattern by
+// joining the static quasis with `*` at each interpolated position, e.g.
+// `import(`./locales/${lang}.json`)` -> `./locales/*.json`.
+fn glob_from_tpl(tpl: &Tpl) -> JsWord {
+  let mut pattern = String::new();
+  for (i, quasi) in tpl.quasis.iter().enumerate() {
+    if i > 0 {
+      pattern.push('*');
+    }
+    let text = quasi.cooked.as_ref().map(|s| s.value.clone()).unwrap_or_else(|| quasi.raw.value.clone());
+    pattern.push_str(text.as_ref());
+  }
+  pattern.into()
+}
+
+// Matches `import(`...${x}...`)`, i.e. a dynamic import whose specifier can't be
+// resolved statically. Unlike `match_import`, which only matches a plain string
+// literal, this only matches a template literal with at least one interpolation.
+fn match_dynamic_import_glob(node: &Expr, ignore_mark: Mark) -> Option<(JsWord, swc_common::Span)> {
+  match node {
+    Expr::Call(call) => {
+      match &call.callee {
+        ExprOrSuper::Expr(expr) => {
+          match &**expr {
+            Expr::Ident(ident) => {
+              if ident.sym == js_word!("import") && !is_marked(ident.span, ignore_mark) {
+                if let Some(arg) = call.args.get(0) {
+                  if let Expr::Tpl(tpl) = &*arg.expr {
+                    if !tpl.exprs.is_empty() {
+                      // The hint comment sits between `(` and the template literal, so it's
+                      // leading trivia of the argument, not of the call itself.
+                      return Some((glob_from_tpl(tpl), tpl.span))
+                    }
+                  }
+                }
+              }
+
+              None
+            },
+            _ => None
+          }
+        },
+        _ => None
+      }
+    },
+    _ => None
+  }
+}
+
+// Reads a leading `/* prefetch */`/`/* preload */` block comment at `pos` (the start of
+// a dynamic `import()`'s template-literal argument) to capture the loading-priority hint
+// for a glob import.
+fn match_chunk_hint(comments: &SingleThreadedComments, pos: swc_common::BytePos) -> Option<JsWord> {
+  let leading = comments.get_leading(pos)?;
+  for comment in leading.iter() {
+    let text = comment.text.trim();
+    if text == "prefetch" || text == "preload" {
+      return Some(text.into())
+    }
+  }
+  None
+}
+
+// Normalizes an `assert { ... }` / `with { ... }` object literal (string-keyed,
+// string-valued per spec) into a sorted attribute map. Shared by the static
+// `ImportDecl`/`NamedExport`/`ExportAll` `.asserts` field and the dynamic
+// `import(specifier, { with: {...} })` second-argument form below, so both flow
+// through the same extraction logic.
+fn import_attrs_from_obj(obj: &ObjectLit) -> ImportAttrs {
+  let mut attrs = BTreeMap::new();
+  for prop in &obj.props {
+    if let PropOrSpread::Prop(prop) = prop {
+      if let Prop::KeyValue(kv) = &**prop {
+        let key = match &kv.key {
+          PropName::Ident(ident) => Some(ident.sym.clone()),
+          PropName::Str(str_) => Some(str_.value.clone()),
+          _ => None
+        };
+        if let (Some(key), Expr::Lit(Lit::Str(str_))) = (key, &*kv.value) {
+          attrs.insert(key, str_.value.clone());
+        }
+      }
+    }
+  }
+  attrs
+}
+
+// The inverse of `import_attrs_from_obj`: rebuilds an `{ key: "value", ... }` object
+// literal so a non-static-form dependency (e.g. a dynamic import) can still carry its
+// attributes on the synthesized `import "abc:..."` marker the same way a static
+// `.asserts` clone does.
+fn import_attrs_to_obj(attrs: &ImportAttrs) -> Option<ObjectLit> {
+  if attrs.is_empty() {
+    return None
+  }
+
+  Some(ObjectLit {
+    span: DUMMY_SP,
+    props: attrs.iter().map(|(key, value)| {
+      PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(Ident::new(key.clone(), DUMMY_SP)),
+        value: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: value.clone(), kind: StrKind::Synthesized, has_escape: false })))
+      })))
+    }).collect()
+  })
+}
+
+// Matches the attributes object off a dynamic `import(specifier, { with: {...} })`
+// call's second argument (also accepts the legacy `assert` key).
+fn match_dynamic_import_attrs(call: &CallExpr) -> ImportAttrs {
+  let with: JsWord = "with".into();
+  let assert: JsWord = "assert".into();
+
+  let arg = match call.args.get(1) {
+    Some(arg) => arg,
+    None => return ImportAttrs::new()
+  };
+  let obj = match &*arg.expr {
+    Expr::Object(obj) => obj,
+    _ => return ImportAttrs::new()
+  };
+
+  for prop in &obj.props {
+    if let PropOrSpread::Prop(prop) = prop {
+      if let Prop::KeyValue(kv) = &**prop {
+        let key = match &kv.key {
+          PropName::Ident(ident) => Some(ident.sym.clone()),
+          PropName::Str(str_) => Some(str_.value.clone()),
+          _ => None
+        };
+        if let (Some(key), Expr::Object(inner)) = (key, &*kv.value) {
+          if key == with || key == assert {
+            return import_attrs_from_obj(inner)
+          }
+        }
+      }
+    }
+  }
+
+  ImportAttrs::new()
+}
+
 fn match_import(node: &Expr, ignore_mark: Mark) -> Option<JsWord> {
   match node {
     Expr::Call(call) => {
@@ -1390,6 +2668,49 @@ fn match_import(node: &Expr, ignore_mark: Mark) -> Option<JsWord> {
   }
 }
 
+// ES2022 allows arbitrary string module export names (`export { x as "a-b" }`,
+// `import { "a-b" as y } from "mod"`, `export * as "ns" from "mod"`), modeled by swc as
+// `ModuleExportName::Str` alongside the usual `ModuleExportName::Ident`.
+fn match_export_name(name: &ModuleExportName) -> (JsWord, swc_common::Span) {
+  match name {
+    ModuleExportName::Ident(ident) => (ident.sym.clone(), ident.span),
+    ModuleExportName::Str(str_) => (str_.value.clone(), str_.span)
+  }
+}
+
+// Like `match_export_name`, but for positions that must be a real local binding
+// (e.g. the non-reexported side of a named export) rather than an arbitrary string.
+fn match_export_name_ident(name: &ModuleExportName) -> &Ident {
+  match name {
+    ModuleExportName::Ident(ident) => ident,
+    ModuleExportName::Str(str_) => unreachable!("invalid module export name used as local binding: {:?}", str_.value)
+  }
+}
+
+// Rewrites `import foo = A.B.C` ts entity names into the equivalent member-access
+// expression (`A.B.C`), so the entity-name form of `TsImportEquals` can be lowered to
+// a plain `var foo = A.B.C;` rather than dropped for having no module specifier to hoist.
+fn ts_entity_name_to_expr(name: &TsEntityName) -> Expr {
+  match name {
+    TsEntityName::Ident(ident) => Expr::Ident(ident.clone()),
+    TsEntityName::TsQualifiedName(qualified) => Expr::Member(MemberExpr {
+      span: DUMMY_SP,
+      obj: ExprOrSuper::Expr(Box::new(ts_entity_name_to_expr(&qualified.left))),
+      prop: Box::new(Expr::Ident(qualified.right.clone())),
+      computed: false
+    })
+  }
+}
+
+fn is_identifier(s: &JsWord) -> bool {
+  let mut chars = s.chars();
+  match chars.next() {
+    Some(c) if c == '$' || c == '_' || c.is_alphabetic() => {},
+    _ => return false
+  }
+  chars.all(|c| c == '$' || c == '_' || c.is_alphanumeric())
+}
+
 fn has_binding_identifier(node: &Pat, sym: &JsWord, decls: &HashSet<IdentId>) -> bool {
   match node {
     Pat::Ident(ident) => {
@@ -1446,14 +2767,232 @@ mod tests {
   extern crate indoc;
   use self::indoc::indoc;
 
-  fn parse(code: &str) -> (Collect, String, HoistResult) {
+  fn parse(code: &str) -> (Collect, String, HoistResult) {
+    parse_lazy(code, HoistLazy::default())
+  }
+
+  fn parse_lazy(code: &str, lazy: HoistLazy) -> (Collect, String, HoistResult) {
+    parse_interop(code, lazy, HoistInterop::default())
+  }
+
+  fn parse_interop(code: &str, lazy: HoistLazy, interop: HoistInterop) -> (Collect, String, HoistResult) {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+  
+    let comments = SingleThreadedComments::default();  
+    let mut esconfig = EsConfig::default();
+    esconfig.dynamic_import = true;
+    esconfig.import_assertions = true;
+    let lexer = Lexer::new(
+      Syntax::Es(esconfig),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+  
+    let mut parser = Parser::new_from(lexer);
+    match parser.parse_module() {
+      Ok(module) => {
+        swc_common::GLOBALS.set(&Globals::new(), || {
+          swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+            let global_mark = Mark::fresh(Mark::root());
+            let module = module.fold_with(&mut resolver_with_mark(global_mark));
+
+            let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+            module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+            
+            let (module, res) = {
+              let mut hoist = Hoist::new("abc", &collect, global_mark, lazy.clone(), interop);
+              let module = module.fold_with(&mut hoist);
+              (module, hoist.get_result())
+            };
+            let code = emit(source_map, comments, &module);
+            (collect, code, res)
+          })
+        })
+      },
+      Err(err) => {
+        panic!("{:?}", err);
+      }
+    }
+  }
+
+  fn parse_typescript(code: &str) -> (Collect, String, HoistResult) {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+
+    let comments = SingleThreadedComments::default();
+    let lexer = Lexer::new(
+      Syntax::Typescript(TsConfig::default()),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    match parser.parse_module() {
+      Ok(module) => {
+        swc_common::GLOBALS.set(&Globals::new(), || {
+          swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+            let global_mark = Mark::fresh(Mark::root());
+            let module = module.fold_with(&mut resolver_with_mark(global_mark));
+
+            let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+            module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+
+            let (module, res) = {
+              let mut hoist = Hoist::new("abc", &collect, global_mark, HoistLazy::default(), HoistInterop::default());
+              let module = module.fold_with(&mut hoist);
+              (module, hoist.get_result())
+            };
+            let code = emit(source_map, comments, &module);
+            (collect, code, res)
+          })
+        })
+      },
+      Err(err) => {
+        panic!("{:?}", err);
+      }
+    }
+  }
+
+  fn parse_system_js(code: &str) -> String {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+
+    let comments = SingleThreadedComments::default();
+    let mut esconfig = EsConfig::default();
+    esconfig.dynamic_import = true;
+    let lexer = Lexer::new(
+      Syntax::Es(esconfig),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+        let global_mark = Mark::fresh(Mark::root());
+        let module = module.fold_with(&mut resolver_with_mark(global_mark));
+
+        let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+        module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+
+        let mut hoist = Hoist::new("abc", &collect, global_mark, HoistLazy::default(), HoistInterop::default());
+        let module = module.fold_with(&mut hoist);
+        let result = hoist.get_result();
+        let module = to_system_js(module, &result, "abc");
+        emit(source_map, comments, &module)
+      })
+    })
+  }
+
+  fn parse_raw(code: &str) -> (Collect, Module, Lrc<SourceMap>, SingleThreadedComments) {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+
+    let comments = SingleThreadedComments::default();
+    let lexer = Lexer::new(
+      Syntax::Es(EsConfig::default()),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+    let collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+    (collect, module, source_map, comments)
+  }
+
+  fn lex(code: &str) -> ModuleInfo {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+
+    let comments = SingleThreadedComments::default();
+    let mut esconfig = EsConfig::default();
+    esconfig.dynamic_import = true;
+    esconfig.import_assertions = true;
+    let lexer = Lexer::new(
+      Syntax::Es(esconfig),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      let global_mark = Mark::fresh(Mark::root());
+      let module = module.fold_with(&mut resolver_with_mark(global_mark));
+      super::lex_module(&module, source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone())
+    })
+  }
+
+  fn parse_shake(code: &str, side_effect_free_modules: HashSet<JsWord>) -> (Collect, String, HoistResult) {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(
+      FileName::Anon,
+      code.into()
+    );
+
+    let comments = SingleThreadedComments::default();
+    let mut esconfig = EsConfig::default();
+    esconfig.dynamic_import = true;
+    let lexer = Lexer::new(
+      Syntax::Es(esconfig),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+        let global_mark = Mark::fresh(Mark::root());
+        let module = module.fold_with(&mut resolver_with_mark(global_mark));
+
+        let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+        module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+
+        let (module, res) = {
+          let mut hoist = Hoist::new("abc", &collect, global_mark, HoistLazy::default(), HoistInterop::default());
+          let module = module.fold_with(&mut hoist);
+          (module, hoist.get_result())
+        };
+        let module = shake(module, &res, &collect, "abc", &side_effect_free_modules, None);
+        let code = emit(source_map, comments, &module);
+        (collect, code, res)
+      })
+    })
+  }
+
+  fn parse_shake_exports(code: &str, used_exports: HashSet<JsWord>, side_effect_free_modules: HashSet<JsWord>) -> (Collect, String, HoistResult) {
     let source_map = Lrc::new(SourceMap::default());
     let source_file = source_map.new_source_file(
       FileName::Anon,
       code.into()
     );
-  
-    let comments = SingleThreadedComments::default();  
+
+    let comments = SingleThreadedComments::default();
     let mut esconfig = EsConfig::default();
     esconfig.dynamic_import = true;
     let lexer = Lexer::new(
@@ -1462,32 +3001,27 @@ mod tests {
       StringInput::from(&*source_file),
       Some(&comments),
     );
-  
-    let mut parser = Parser::new_from(lexer);
-    match parser.parse_module() {
-      Ok(module) => {
-        swc_common::GLOBALS.set(&Globals::new(), || {
-          swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
-            let global_mark = Mark::fresh(Mark::root());
-            let module = module.fold_with(&mut resolver_with_mark(global_mark));
 
-            let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()));
-            module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
-            
-            let (module, res) = {
-              let mut hoist = Hoist::new("abc", &collect, global_mark);
-              let module = module.fold_with(&mut hoist);
-              (module, hoist.get_result())
-            };
-            let code = emit(source_map, comments, &module);
-            (collect, code, res)
-          })
-        })
-      },
-      Err(err) => {
-        panic!("{:?}", err);
-      }
-    }
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+        let global_mark = Mark::fresh(Mark::root());
+        let module = module.fold_with(&mut resolver_with_mark(global_mark));
+
+        let mut collect = Collect::new(source_map.clone(), collect_decls(&module), Mark::fresh(Mark::root()), comments.clone());
+        module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+
+        let (module, res) = {
+          let mut hoist = Hoist::new("abc", &collect, global_mark, HoistLazy::default(), HoistInterop::default());
+          let module = module.fold_with(&mut hoist);
+          (module, hoist.get_result())
+        };
+        let module = shake(module, &res, &collect, "abc", &side_effect_free_modules, Some(&used_exports));
+        let code = emit(source_map, comments, &module);
+        (collect, code, res)
+      })
+    })
   }
 
   fn emit(source_map: Lrc<SourceMap>, comments: SingleThreadedComments, program: &Module) -> String {
@@ -1528,6 +3062,18 @@ mod tests {
     };
   );
 
+  macro_rules! btreemap(
+    { $($key:expr => $value:expr),* } => {
+      {
+        let mut m = BTreeMap::new();
+        $(
+          m.insert($key, $value);
+        )*
+        m
+      }
+    };
+  );
+
   macro_rules! set(
     { $($key:expr),* } => {
       {
@@ -2269,6 +3815,21 @@ mod tests {
     assert_eq!(code, indoc!{r#"
     import   "abc:bar";
     "#});
+
+    let (_collect, code, hoist) = parse(r#"
+    export * as ns from 'bar';
+    "#);
+
+    // Unlike a plain `export {x} from`, the namespace form also materializes a real
+    // local binding for `ns`, since it names the whole namespace object of `bar` rather
+    // than a single passthrough value the linker can alias directly.
+    let ns_import: JsWord = format!("$abc$import${:x}", hash!(w!("bar"))).into();
+    assert_eq!(code, format!("import   \"abc:bar\";\nvar $abc$export$ns = {};\n", ns_import));
+    assert_eq!(hoist.exported_symbols.get(&w!("ns")).unwrap().0, w!("$abc$export$ns"));
+    assert_eq!(hoist.re_exports.len(), 1);
+    assert_eq!(hoist.re_exports[0].0, w!("ns"));
+    assert_eq!(hoist.re_exports[0].1, w!("bar"));
+    assert_eq!(hoist.re_exports[0].2, w!("*"));
   }
 
   #[test]
@@ -2440,7 +4001,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2460,7 +4021,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), w!("*"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2480,7 +4041,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2500,7 +4061,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2517,7 +4078,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2532,7 +4093,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), w!("*"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2547,7 +4108,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2562,7 +4123,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2577,7 +4138,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2593,7 +4154,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), w!("*"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2609,7 +4170,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2624,7 +4185,7 @@ mod tests {
       w!("$abc$importAsync$558d6cfb8af8a010$ba02ad2230917043") => (w!("other"), w!("foo"))
     });
     assert_eq!(hoist.dynamic_imports, map!{
-      w!("$abc$importAsync$558d6cfb8af8a010") => w!("other")
+      w!("$abc$importAsync$558d6cfb8af8a010") => (w!("other"), BTreeMap::new())
     });
     assert_eq!(code, indoc!{r#"
     import   "abc:other";
@@ -2632,4 +4193,573 @@ mod tests {
     });
     "#});
   }
+
+  #[test]
+  fn top_level_await() {
+    let (_collect, _code, hoist) = parse(r#"
+    await foo();
+    "#);
+    assert_eq!(hoist.has_top_level_await, true);
+
+    let (_collect, _code, hoist) = parse(r#"
+    for await (const x of foo()) {
+      bar(x);
+    }
+    "#);
+    assert_eq!(hoist.has_top_level_await, true);
+
+    let (_collect, _code, hoist) = parse(r#"
+    async function foo() {
+      await bar();
+    }
+    "#);
+    assert_eq!(hoist.has_top_level_await, false);
+
+    let (_collect, _code, hoist) = parse(r#"
+    const foo = async () => {
+      await bar();
+    };
+    "#);
+    assert_eq!(hoist.has_top_level_await, false);
+  }
+
+  #[test]
+  fn collect_top_level_await() {
+    let (collect, _code, _hoist) = parse(r#"
+    await foo();
+    "#);
+    assert_eq!(collect.has_top_level_await, true);
+
+    let (collect, _code, _hoist) = parse(r#"
+    for await (const x of foo()) {
+      bar(x);
+    }
+    "#);
+    assert_eq!(collect.has_top_level_await, true);
+
+    let (collect, _code, _hoist) = parse(r#"
+    async function foo() {
+      await bar();
+    }
+    "#);
+    assert_eq!(collect.has_top_level_await, false);
+
+    let (collect, _code, _hoist) = parse(r#"
+    const foo = async () => {
+      await bar();
+    };
+    "#);
+    assert_eq!(collect.has_top_level_await, false);
+  }
+
+  #[test]
+  fn lazy_require() {
+    let (_collect, code, hoist) = parse_lazy(r#"
+    const x = require('other');
+    console.log(x.foo);
+    "#, HoistLazy::Bool(true));
+
+    assert_eq!(hoist.lazy_imports, set!{ w!("other") });
+    assert_eq!(code, indoc!{r#"
+    console.log($abc$import$558d6cfb8af8a010$ba02ad2230917043);
+    "#});
+
+    let (_collect, _code, hoist) = parse_lazy(r#"
+    const x = require('other');
+    console.log(x.foo);
+    const y = require('another');
+    console.log(y.bar);
+    "#, HoistLazy::Sources(set!{ w!("other") }));
+
+    assert_eq!(hoist.lazy_imports, set!{ w!("other") });
+  }
+
+  #[test]
+  fn interop() {
+    let (_collect, code, hoist) = parse_interop(r#"
+    import foo from 'other';
+    console.log(foo);
+    "#, HoistLazy::default(), HoistInterop { no_interop: true, strict: false });
+
+    assert_eq!(hoist.needs_default_interop, set!{});
+    assert_eq!(code, indoc!{r#"
+    import   "abc:other";
+    console.log($abc$import$558d6cfb8af8a010);
+    "#});
+
+    let (_collect, code, hoist) = parse_interop(r#"
+    import foo from 'other';
+    console.log(foo);
+    "#, HoistLazy::default(), HoistInterop { no_interop: false, strict: true });
+
+    let default_ident: JsWord = format!("$abc$import${:x}${:x}", hash!(w!("other")), hash!(w!("default"))).into();
+    assert_eq!(hoist.needs_default_interop, set!{ default_ident.clone() });
+    assert_eq!(code, format!("import   \"abc:other\";\nconsole.log({});\n", default_ident));
+  }
+
+  #[test]
+  fn no_interop_named_imports() {
+    // `no_interop` only changes how `default` resolves; named access through a CJS
+    // module's namespace must keep working exactly as without the flag.
+    let (_collect, code, _hoist) = parse_interop(r#"
+    import * as ns from 'other';
+    console.log(ns.foo, ns.default);
+    "#, HoistLazy::default(), HoistInterop { no_interop: true, strict: false });
+
+    let foo_ident: JsWord = format!("$abc$import${:x}${:x}", hash!(w!("other")), hash!(w!("foo"))).into();
+    assert_eq!(code, format!("import   \"abc:other\";\nconsole.log({}, $abc$import$558d6cfb8af8a010);\n", foo_ident));
+  }
+
+  #[test]
+  fn export_star_sources() {
+    let (_collect, _code, hoist) = parse(r#"
+    export * from 'a';
+    export * from 'b';
+    "#);
+    assert_eq!(hoist.star_sources, vec![w!("a"), w!("b")]);
+
+    let (_collect, _code, hoist) = parse(r#"
+    export * from 'a';
+    export * from 'a';
+    "#);
+    assert_eq!(hoist.star_sources, vec![w!("a")]);
+  }
+
+  #[test]
+  fn import_assertions() {
+    let (_collect, _code, hoist) = parse(r#"
+    import data from './data.json' assert { type: 'json' };
+    console.log(data);
+    "#);
+
+    assert_eq!(hoist.import_assertions, map!{
+      w!("abc:./data.json") => btreemap!{ w!("type") => w!("json") }
+    });
+  }
+
+  #[test]
+  fn dynamic_import_attrs() {
+    let (_collect, _code, hoist) = parse(r#"
+    async function load() {
+      const data = await import('./data.json', { with: { type: 'json' } });
+      console.log(data);
+    }
+    "#);
+
+    let (source, attrs) = hoist.dynamic_imports.values().next().unwrap();
+    assert_eq!(source, &w!("./data.json"));
+    assert_eq!(attrs, &btreemap!{ w!("type") => w!("json") });
+  }
+
+  #[test]
+  fn dynamic_import_attrs_distinct_from_static() {
+    // Two references to the same specifier with different attributes must not collapse
+    // into a single dependency record.
+    let (_collect, _code, hoist) = parse(r#"
+    import data from './data.json' assert { type: 'json' };
+    async function load() {
+      const other = await import('./data.json', { with: { type: 'url' } });
+      console.log(data, other);
+    }
+    "#);
+
+    let (_source, dynamic_attrs) = hoist.dynamic_imports.values().next().unwrap();
+    assert_eq!(dynamic_attrs, &btreemap!{ w!("type") => w!("url") });
+
+    let (_source, _key, _loc, static_attrs) = hoist.imported_symbols.values()
+      .find(|(source, _key, _loc, _attrs)| source == &w!("./data.json"))
+      .unwrap();
+    assert_eq!(static_attrs, &btreemap!{ w!("type") => w!("json") });
+  }
+
+  #[test]
+  fn lazy_wrapped_require() {
+    // A require accessed statically inside a function body is wrapped (to preserve side
+    // effect ordering) but not non-static, so lazy mode can defer its initialization.
+    let (_collect, _code, hoist) = parse_lazy(r#"
+    function test() {
+      return require('other').foo;
+    }
+    "#, HoistLazy::Bool(true));
+    assert_eq!(hoist.lazy_imports, set!{ w!("other") });
+
+    // A non-statically-destructured require must stay eager even in lazy mode.
+    let (_collect, _code, hoist) = parse_lazy(r#"
+    function test() {
+      const {foo, ...rest} = require('other');
+      return foo;
+    }
+    "#, HoistLazy::Bool(true));
+    assert_eq!(hoist.lazy_imports, set!{});
+  }
+
+  #[test]
+  fn require_resolve() {
+    let (collect, code, hoist) = parse(r#"
+    const p = require.resolve('other');
+    "#);
+    assert_eq!(collect.resolves, set!{ w!("other") });
+    assert_eq!(hoist.resolves, set!{ w!("other") });
+    assert!(code.contains("require.resolve(\"abc:other\")"));
+  }
+
+  #[test]
+  fn optional_requires() {
+    let (collect, _code, hoist) = parse(r#"
+    let native;
+    try {
+      native = require('fsevents');
+    } catch (err) {}
+
+    try {
+      require.resolve('optional-resolve');
+    } catch (err) {}
+
+    require('eager');
+    "#);
+
+    assert_eq!(collect.optional_requires, set!{ w!("fsevents"), w!("optional-resolve") });
+    assert_eq!(hoist.optional_requires, set!{ w!("fsevents"), w!("optional-resolve") });
+  }
+
+  #[test]
+  fn glob_imports() {
+    let (collect, code, hoist) = parse(r#"
+    async function load(lang) {
+      let messages = await import(`./locales/${lang}.json`);
+    }
+    "#);
+
+    assert_eq!(collect.glob_requires, set!{ w!("./locales/*.json") });
+    assert_eq!(hoist.glob_requires, set!{ w!("./locales/*.json") });
+    assert!(code.contains("$parcel$importGlob(\"abc:./locales/*.json\", `./locales/${lang}.json`)"));
+  }
+
+  #[test]
+  fn glob_import_chunk_hint() {
+    let (collect, _code, hoist) = parse(r#"
+    async function load(lang) {
+      let messages = await import(/* prefetch */ `./locales/${lang}.json`);
+    }
+    "#);
+
+    assert_eq!(collect.chunk_hints.get(&w!("./locales/*.json")), Some(&w!("prefetch")));
+    assert_eq!(hoist.chunk_hints.get(&w!("./locales/*.json")), Some(&w!("prefetch")));
+  }
+
+  #[test]
+  fn ts_export_assignment() {
+    let (collect, code, hoist) = parse_typescript(r#"
+    function foo() {}
+    export = foo;
+    "#);
+
+    assert_eq!(collect.has_cjs_exports, true);
+    assert_eq!(collect.static_cjs_exports, false);
+    assert_eq!(hoist.has_cjs_exports, true);
+    assert_eq!(hoist.static_cjs_exports, false);
+    assert_eq!(code, indoc!{r#"
+    function $abc$var$foo() {
+    }
+    var $abc$exports = $abc$var$foo;
+    "#});
+  }
+
+  #[test]
+  fn ts_import_equals() {
+    let (collect, code, _hoist) = parse_typescript(r#"
+    import foo = require('other');
+    console.log(foo.bar);
+    "#);
+
+    assert_eq_imports!(collect.imports, map!{ w!("foo") => (w!("other"), w!("*"), false) });
+    assert!(code.contains("import   \"abc:other\";"));
+    assert!(!code.contains("import foo"));
+  }
+
+  #[test]
+  fn ts_import_equals_entity_name() {
+    let (_collect, code, _hoist) = parse_typescript(r#"
+    import foo = SomeNamespace.Member;
+    console.log(foo);
+    "#);
+
+    // There's no module specifier to hoist here, just a value binding that must keep
+    // resolving to whatever `SomeNamespace.Member` pointed to.
+    assert!(code.contains("var $abc$var$foo = SomeNamespace.Member;"));
+    assert!(!code.contains("import foo"));
+  }
+
+  #[test]
+  fn ts_type_only_elision() {
+    let (_collect, code, _hoist) = parse_typescript(r#"
+    import type { A } from 'only-types';
+    import { type B, C } from 'mixed';
+    console.log(C);
+    "#);
+
+    // `only-types` is referenced solely by a type-only specifier, so it never gets hoisted.
+    assert!(!code.contains("only-types"));
+    // `mixed` still has a value specifier (`C`), so it keeps being imported.
+    assert!(code.contains("mixed"));
+  }
+
+  #[test]
+  fn string_export_names() {
+    let (_collect, _code, hoist) = parse(r#"
+    const x = 1;
+    export { x as "a-b" };
+    "#);
+    let exported_ident: JsWord = format!("$abc$export${:x}", hash!(w!("a-b"))).into();
+    assert_eq!(hoist.exported_symbols.get(&w!("a-b")).unwrap().0, exported_ident);
+
+    let (_collect, _code, hoist) = parse(r#"
+    export * as "ns" from "other";
+    "#);
+    assert_eq!(hoist.re_exports.len(), 1);
+    assert_eq!(hoist.re_exports[0].0, w!("ns"));
+    assert_eq!(hoist.re_exports[0].1, w!("other"));
+    assert_eq!(hoist.re_exports[0].2, w!("*"));
+  }
+
+  #[test]
+  fn conflicting_exports_resolution() {
+    let (_collect, _code, hoist) = parse(r#"
+    export * from 'a';
+    export * from 'b';
+    "#);
+
+    let source_exports = map!{
+      w!("a") => set!{w!("shared"), w!("onlyA"), w!("default")},
+      w!("b") => set!{w!("shared"), w!("onlyB")}
+    };
+    let conflicting = hoist.conflicting_exports(&source_exports);
+
+    // `shared` is claimed by both star sources, so it's ambiguous...
+    assert!(conflicting.contains(&w!("shared")));
+    // ...but names unique to one star source are fine...
+    assert!(!conflicting.contains(&w!("onlyA")));
+    assert!(!conflicting.contains(&w!("onlyB")));
+    // ...and `default` is never part of `export *` in the first place.
+    assert!(!conflicting.contains(&w!("default")));
+
+    let (_collect, _code, hoist) = parse(r#"
+    export * from 'a';
+    export * from 'b';
+    export const shared = 1;
+    "#);
+
+    let source_exports = map!{
+      w!("a") => set!{w!("shared")},
+      w!("b") => set!{w!("shared")}
+    };
+    // This module's own explicit export of `shared` always wins over the star sources,
+    // so it isn't ambiguous even though both star sources claim it too.
+    assert!(!hoist.conflicting_exports(&source_exports).contains(&w!("shared")));
+  }
+
+  #[test]
+  fn system_js() {
+    let code = parse_system_js(r#"
+    import { foo } from 'other';
+    export let y = foo + 1;
+    y = y + 1;
+    "#);
+
+    // The dependency array comes from the hoisted `import "abc:..."` markers; the
+    // setters are built from `imported_symbols`.
+    assert!(code.contains("System.register(["));
+    assert!(code.contains("\"other\""));
+    assert!(code.contains("function ($$module)"));
+    // Every write to a live export is routed through `_export`.
+    assert!(code.contains("_export(\"y\""));
+    assert!(code.contains("function (_export, _context)"));
+  }
+
+  #[test]
+  #[should_panic(expected = "does not support lazy-initialized requires")]
+  fn system_js_rejects_lazy_mode() {
+    let (_collect, module, _source_map, _comments) = parse_raw("");
+    let result = HoistResult { lazy_imports: set!{ w!("other") }, ..HoistResult::default() };
+    super::to_system_js(module, &result, "abc");
+  }
+
+  #[test]
+  #[should_panic(expected = "does not support lazy-initialized requires")]
+  fn hoist_rejects_lazy_with_system_js_output() {
+    let (_collect, module, source_map, comments) = parse_raw("import {x} from 'other'; console.log(x);");
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      swc_ecmascript::transforms::helpers::HELPERS.set(&swc_ecmascript::transforms::helpers::Helpers::new(false), || {
+        let global_mark = Mark::fresh(Mark::root());
+        let module = module.fold_with(&mut resolver_with_mark(global_mark));
+        let decls = collect_decls(&module);
+        super::hoist(module, source_map, "abc", decls, Mark::fresh(Mark::root()), global_mark, HoistLazy::Bool(true), HoistInterop::default(), HoistOutputMode::SystemJs, HashSet::new(), comments, None);
+      })
+    });
+  }
+
+  #[test]
+  fn shake_removes_unused() {
+    let (_collect, code, _hoist) = parse_shake(r#"
+    import { unused } from 'other';
+    import { used } from 'used';
+    const dead = 1;
+    console.log(used);
+    "#, set!{w!("other")});
+
+    // `other` is unreferenced and flagged side-effect-free, so both the specifier
+    // and its hoisted import statement disappear.
+    assert!(!code.contains("other"));
+    assert!(!code.contains("dead"));
+    assert!(code.contains("used"));
+  }
+
+  #[test]
+  fn shake_keeps_non_side_effect_free_import() {
+    let (_collect, code, _hoist) = parse_shake(r#"
+    import 'other';
+    console.log(1);
+    "#, HashSet::new());
+
+    // `other` isn't in `side_effect_free_modules`, so it must be kept even though
+    // nothing references it - it may run side effects on its own.
+    assert_eq!(code, "import   \"abc:other\";\nconsole.log(1);\n".to_string());
+  }
+
+  #[test]
+  fn shake_removes_dead_declarator_from_multi_decl() {
+    let (_collect, code, _hoist) = parse_shake(r#"
+    const dead = 1, used = 2;
+    console.log(used);
+    "#, HashSet::new());
+
+    // Only the dead declarator is dropped; its live sibling in the same `const`
+    // statement must survive.
+    assert!(!code.contains("dead"));
+    assert!(code.contains("used"));
+  }
+
+  #[test]
+  fn shake_prunes_unreferenced_dynamic_import() {
+    // `unused` captures the generated `$abc$importAsync$...` binding directly (rather
+    // than a bare `import('other');` statement, whose own substituted identifier would
+    // always count as a use of itself and could never reach a zero count), so once
+    // `unused` is dropped for being dead, the dynamic import it referenced loses its
+    // only remaining use too.
+    let (_collect, code, _hoist) = parse_shake(r#"
+    const unused = import('other');
+    console.log("side effect");
+    "#, set!{w!("other")});
+
+    assert!(!code.contains("other"));
+    assert!(code.contains("side effect"));
+  }
+
+  #[test]
+  fn shake_exports_drops_unused_export_and_its_dependents() {
+    let (_collect, code, _hoist) = parse_shake_exports(r#"
+    import { helper } from 'other';
+    const unused = helper;
+    export { unused };
+    console.log("side effect");
+    "#, HashSet::new(), set!{w!("other")});
+
+    // Neither the caller's requested exports nor anything else in the module reaches
+    // `unused`, so it - and the import it alone depended on - fall out of the same
+    // fixpoint as ordinary same-module DCE.
+    assert!(!code.contains("unused"));
+    assert!(!code.contains("other"));
+    assert!(code.contains("side effect"));
+  }
+
+  #[test]
+  fn shake_exports_keeps_requested_export() {
+    let (_collect, code, _hoist) = parse_shake_exports(r#"
+    export const kept = 1;
+    "#, set!{w!("kept")}, HashSet::new());
+
+    assert!(code.contains("kept"));
+  }
+
+  #[test]
+  fn codegen_config() {
+    let (_collect, module, source_map, comments) = parse_raw("const x = 1 + 2;\n");
+
+    let (code, map) = super::emit(&module, source_map.clone(), &comments, &CodegenConfig::default());
+    assert_eq!(code, "const x = 1 + 2;\n");
+    assert!(map.is_none());
+
+    let (code, map) = super::emit(&module, source_map.clone(), &comments, &CodegenConfig { minify: true, ..CodegenConfig::default() });
+    assert!(!code.contains('\n'));
+    assert!(map.is_none());
+
+    let (_code, map) = super::emit(&module, source_map.clone(), &comments, &CodegenConfig { source_maps: true, ..CodegenConfig::default() });
+    assert!(map.is_some());
+  }
+
+  #[test]
+  fn lex_module_imports() {
+    let info = lex(r#"
+    import def, { named as alias } from 'a';
+    import * as ns from 'b';
+    const c = await import('c');
+    "#);
+
+    assert_eq!(info.imports.len(), 4);
+    assert!(info.imports.iter().any(|(source, imported, is_dynamic, _loc)| source == &w!("a") && imported == &w!("default") && !is_dynamic));
+    assert!(info.imports.iter().any(|(source, imported, is_dynamic, _loc)| source == &w!("a") && imported == &w!("named") && !is_dynamic));
+    assert!(info.imports.iter().any(|(source, imported, is_dynamic, _loc)| source == &w!("b") && imported == &w!("*") && !is_dynamic));
+    assert!(info.imports.iter().any(|(source, imported, is_dynamic, _loc)| source == &w!("c") && imported == &w!("*") && *is_dynamic));
+  }
+
+  #[test]
+  fn lex_module_re_exports() {
+    let info = lex(r#"
+    export { a, b as c } from 'x';
+    export * from 'y';
+    export * from 'y';
+    "#);
+
+    let stripped: Vec<(JsWord, JsWord, JsWord)> = info.re_exports.iter().map(|(exported, source, orig, _loc)| {
+      (exported.clone(), source.clone(), orig.clone())
+    }).collect();
+    assert_eq!(stripped, vec![
+      (w!("a"), w!("x"), w!("a")),
+      (w!("c"), w!("x"), w!("b")),
+      (w!("*"), w!("y"), w!("*")),
+      // `re_exports` records one entry per `export * from` occurrence (mirroring
+      // `Hoist`'s own un-deduplicated re-export bookkeeping) - only `star_sources`
+      // dedups distinct sources.
+      (w!("*"), w!("y"), w!("*"))
+    ]);
+    assert_eq!(info.star_sources, vec![w!("y")]);
+  }
+
+  #[test]
+  fn lex_module_exports_and_cjs() {
+    let info = lex(r#"
+    export const a = 1;
+    export function b() {}
+    "#);
+    assert_eq!(info.exports.len(), 2);
+    assert!(info.exports.contains(&w!("a")));
+    assert!(info.exports.contains(&w!("b")));
+    assert_eq!(info.has_cjs_exports, false);
+
+    let info = lex("exports.foo = 1;");
+    assert_eq!(info.has_cjs_exports, true);
+  }
+
+  #[test]
+  fn lex_module_top_level_await() {
+    let info = lex("await foo();");
+    assert_eq!(info.has_top_level_await, true);
+
+    let info = lex(r#"
+    async function foo() {
+      await bar();
+    }
+    "#);
+    assert_eq!(info.has_top_level_await, false);
+  }
 }